@@ -2,13 +2,42 @@
 // Application State and Logic - Optimized
 // ============================================================================
 
-use crate::db::Database;
-use crate::system::{AppEntry, scan_apps, fuzzy_search_apps, FileSearcher};
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, TryRecvError};
+use std::time::{Duration, Instant};
+use unicode_segmentation::UnicodeSegmentation;
+use crate::config::Config;
+use crate::db::{Database, HistoryEntry};
+use crate::error::Result;
+use crate::index::SearchScope;
+use crate::plugin::{plugin_command, run_plugin, PluginItem};
+use crate::system::{
+    AppEntry, BrowseEntry, GrepMatch, load_cached_apps, spawn_app_scan, search_apps, fuzzy_search_entries, MatchCache,
+    fuzzy_search_history, fuzzy_search_paths,
+    list_directory, matches_category, with_config_entries, scan_projects, split_category_filters, split_launch_args, zoxide_add,
+    zoxide_dirs, copy_to_clipboard, is_text_file, move_to_trash, read_preview, reveal_command, FileSearcher, SearchJob,
+};
+
+/// (program, args, is_tui)
+pub type LaunchCommand = (String, Vec<String>, bool);
+
+/// Base score change per +/- press in Apps mode
+pub const SCORE_STEP: i32 = 10;
+
+/// How long a confirmation like "Copied" stays in the banner row
+const FLASH_DURATION: Duration = Duration::from_millis(1500);
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum Mode {
     Apps,
     Files,
+    Grep,
+    Browse,
+    Zoxide,
+    Projects,
+    History,
+    /// Index into `config.plugins`
+    Plugin(usize),
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -17,80 +46,455 @@ pub enum InputMode {
     Insert,
 }
 
+/// Entries of the file action menu (Ctrl+O / `o`)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum FileAction {
+    Editor,
+    Default,
+    CopyPath,
+    OpenWith,
+    Reveal,
+    Trash,
+}
+
+impl FileAction {
+    pub const ALL: [FileAction; 6] = [
+        FileAction::Editor,
+        FileAction::Default,
+        FileAction::OpenWith,
+        FileAction::CopyPath,
+        FileAction::Reveal,
+        FileAction::Trash,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            FileAction::Editor => "Open in editor",
+            FileAction::Default => "Open with default app",
+            FileAction::OpenWith => "Open with…",
+            FileAction::CopyPath => "Copy path",
+            FileAction::Reveal => "Reveal in file manager",
+            FileAction::Trash => "Move to trash",
+        }
+    }
+
+    /// Shortcut that runs the action straight from the menu
+    pub fn key(self) -> char {
+        match self {
+            FileAction::Editor => 'e',
+            FileAction::Default => 'o',
+            FileAction::OpenWith => 'w',
+            FileAction::CopyPath => 'c',
+            FileAction::Reveal => 'r',
+            FileAction::Trash => 'd',
+        }
+    }
+}
+
+/// Everything that decides how an app launches and ranks, for the details panel
+pub struct AppDetails {
+    pub name: String,
+    pub exec: String,
+    /// Source .desktop file; empty for $PATH executables and config entries
+    pub desktop_path: String,
+    pub categories: Vec<String>,
+    pub usage: i32,
+    pub last_used: u64,
+    pub is_tui: bool,
+    /// Whether `is_tui` is a stored preference rather than the entry's default
+    pub tui_stored: bool,
+    pub seed_score: i32,
+    pub frecency: i32,
+    pub total_score: i32,
+    pub is_dormant: bool,
+}
+
+/// File waiting for an app from the "Open with" picker, and the view to
+/// return to if the pick is cancelled
+pub struct OpenWith {
+    pub path: String,
+    return_mode: Mode,
+    return_input: String,
+    return_index: usize,
+}
+
+/// Open file action menu and the file it acts on
+pub struct FileMenu {
+    pub path: String,
+    pub selected: usize,
+}
+
+/// Cursor motions on the query in normal mode
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Motion {
+    WordForward,
+    WordBackward,
+    WordEnd,
+    LineStart,
+    LineEnd,
+}
+
+/// Normal mode operators that take a motion, as in `dw` or `ciw`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Operator {
+    Delete,
+    Change,
+}
+
+/// An operator key waiting for its motion; `inner` once the `i` of a text object followed
+#[derive(Debug, Clone, Copy)]
+pub struct PendingOperator {
+    pub operator: Operator,
+    pub inner: bool,
+}
+
 pub struct App {
     // Input state
     pub input: String,
     pub input_mode: InputMode,
-    pub cursor_pos: usize,
+    pub cursor_pos: usize,            // In graphemes, not bytes or chars
+    pub pending_operator: Option<PendingOperator>,
+    query_history: Vec<String>,       // This mode's past queries, newest first
+    query_pos: Option<usize>,         // Entry of query_history shown in the input
 
     // Mode state
     pub mode: Mode,
     pub selected_index: usize,
     pub show_dormant: bool,
+    pub hidden_dormant: usize, // Apps matching the query but hidden as dormant
+    pub marked: Vec<String>, // Keys of marked results (see mark_key), in marking order
 
     // Data
     pub all_apps: Vec<AppEntry>,
     pub filtered_apps: Vec<AppEntry>,
+    app_matches: MatchCache, // Cleared whenever all_apps changes
     pub filtered_files: Vec<String>,
+    pub filtered_matches: Vec<GrepMatch>,
+
+    // Background app scan (then live rescans), merged into all_apps as results arrive
+    app_scan: Option<Receiver<Vec<AppEntry>>>,
+    scan_pending: bool,
+
+    // Directory browser state
+    pub browse_dir: PathBuf,
+    pub browse_entries: Vec<BrowseEntry>,
+    pub filtered_entries: Vec<BrowseEntry>,
+
+    // Directory jump state (zoxide and projects share the filtered list)
+    pub zoxide_dirs: Vec<String>,
+    pub all_projects: Vec<String>,
+    pub filtered_dirs: Vec<String>,
+
+    // Past launches, re-runnable as-is
+    pub history: Vec<HistoryEntry>,
+    pub filtered_history: Vec<HistoryEntry>,
 
-    // File searcher (lazy, streaming)
+    // File searcher (lazy, streaming), started once typing pauses
     file_searcher: FileSearcher,
+    search_due: Option<Instant>,
 
-    // Database
+    // Files mode preview of the selected file, read in the background
+    pub show_preview: bool,
+    pub preview: Vec<String>,
+    pub preview_path: Option<String>,
+    preview_job: SearchJob<String>,
+
+    // Plugin mode results, streamed from the plugin process
+    pub plugin_items: Vec<PluginItem>,
+    plugin_search: SearchJob<PluginItem>,
+
+    // Database & config
     pub db: Database,
+    pub config: Config,
 
     // UI State
     pub should_quit: bool,
     pub should_launch: bool,
-    pub launch_command: Option<(String, Vec<String>, bool)>, // (program, args, is_tui)
+    pub launch_command: Option<LaunchCommand>,
+    pub launched_app: Option<String>, // Name of the app entry behind launch_command, if any
+    launch_batch: Vec<(LaunchCommand, Option<String>)>, // Marked launches, each with its app name
+    pub stay_after_launch: bool,      // Set by Alt+Enter for a single launch
+    pub launch_error: Option<String>, // Shown as a banner until the next key press
+    pub private: bool,                // --private: launches leave no usage, history or zoxide trace
+    pub flash: Option<(String, Instant)>, // Brief confirmation in the banner row, and when it was shown
+    pub file_menu: Option<FileMenu>,
+    pub app_details: Option<AppDetails>,
+    pub open_with: Option<OpenWith>,
 }
 
 impl App {
-    pub fn new() -> Self {
-        let db = Database::new().expect("Failed to initialize database");
+    pub fn new() -> Result<Self> {
+        let db = Database::new()?;
+        let config = Config::load()?;
         
-        // Only load apps on startup - files are lazy-loaded
-        let apps = scan_apps(&db);
+        // Show cached apps immediately and refresh them in the background -
+        // files are lazy-loaded
+        let apps = with_config_entries(load_cached_apps(&db, &config.apps), &config, &db);
+        let mut app = Self::with_apps(db, config, apps);
+        app.app_scan = Some(spawn_app_scan(app.config.apps.clone()));
+        app.scan_pending = true;
+        Ok(app)
+    }
 
+    /// Fixed apps, an in-memory database and no background scan, for tests
+    #[cfg(test)]
+    pub fn headless(config: Config, apps: Vec<AppEntry>) -> Self {
+        let db = Database::in_memory().expect("Failed to initialize database");
+        let apps = with_config_entries(apps, &config, &db);
+        Self::with_apps(db, config, apps)
+    }
+
+    fn with_apps(db: Database, config: Config, apps: Vec<AppEntry>) -> Self {
         Self {
             input: String::new(),
             input_mode: InputMode::Insert,
             cursor_pos: 0,
+            pending_operator: None,
+            query_history: Vec::new(),
+            query_pos: None,
             mode: Mode::Apps,
             selected_index: 0,
             show_dormant: false,
+            hidden_dormant: 0,
+            marked: Vec::new(),
             all_apps: apps.clone(),
             filtered_apps: apps,
+            app_matches: MatchCache::default(),
             filtered_files: Vec::new(), // Start empty
-            file_searcher: FileSearcher::new(),
+            filtered_matches: Vec::new(),
+            app_scan: None,
+            scan_pending: false,
+            browse_dir: dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")),
+            browse_entries: Vec::new(), // Listed on first entry into Browse mode
+            filtered_entries: Vec::new(),
+            zoxide_dirs: Vec::new(), // Queried on first entry into Zoxide mode
+            all_projects: Vec::new(), // Loaded on first entry into Projects mode
+            filtered_dirs: Vec::new(),
+            history: Vec::new(), // Read on every entry into History mode
+            filtered_history: Vec::new(),
+            file_searcher: FileSearcher::new(SearchScope::from_config(&config.files)),
+            search_due: None,
+            show_preview: config.files.preview,
+            preview: Vec::new(),
+            preview_path: None,
+            preview_job: SearchJob::new(),
+            plugin_items: Vec::new(),
+            plugin_search: SearchJob::new(),
             db,
+            config,
             should_quit: false,
             should_launch: false,
             launch_command: None,
+            launched_app: None,
+            launch_batch: Vec::new(),
+            stay_after_launch: false,
+            launch_error: None,
+            private: false,
+            flash: None,
+            file_menu: None,
+            app_details: None,
+            open_with: None,
         }
     }
 
+    // =========================================================================
+    // Background Scanning
+    // =========================================================================
+
+    /// The initial scan hasn't reported back yet
+    pub fn is_scanning(&self) -> bool {
+        self.scan_pending
+    }
+
+    /// The background thread is still alive and may deliver new app lists
+    pub fn is_watching(&self) -> bool {
+        self.app_scan.is_some()
+    }
+
+    /// Merge a finished background scan. Returns true if the app list changed.
+    pub fn poll_app_scan(&mut self) -> bool {
+        let result = match &self.app_scan {
+            Some(rx) => rx.try_recv(),
+            None => return false,
+        };
+
+        match result {
+            Ok(apps) => {
+                self.scan_pending = false;
+
+                // Keep the cursor on the same app if the user already moved it
+                let selected = self
+                    .filtered_apps
+                    .get(self.selected_index)
+                    .map(|a| a.name.clone());
+
+                self.all_apps = with_config_entries(apps, &self.config, &self.db);
+                self.app_matches.clear();
+                if self.mode == Mode::Apps {
+                    self.update_search();
+                    if let Some(name) = selected {
+                        if let Some(i) = self.filtered_apps.iter().position(|a| a.name == name) {
+                            self.selected_index = i;
+                        }
+                    }
+                }
+                true
+            }
+            Err(TryRecvError::Empty) => false,
+            Err(TryRecvError::Disconnected) => {
+                // Scan thread died - keep showing whatever we have
+                self.app_scan = None;
+                self.scan_pending = false;
+                true
+            }
+        }
+    }
+
+    /// A file, content or plugin search is running on a worker thread
+    pub fn is_searching(&self) -> bool {
+        self.file_searcher.is_searching() || self.plugin_search.is_running() || self.preview_job.is_running()
+    }
+
+    /// Time left before a debounced search starts
+    pub fn search_delay(&self) -> Option<Duration> {
+        self.search_due
+            .map(|due| due.saturating_duration_since(Instant::now()))
+    }
+
+    /// Start a debounced search once it is due, then merge the newest streamed
+    /// file, content or plugin results. Returns true if any arrived.
+    pub fn poll_search(&mut self) -> bool {
+        if self.search_due.is_some_and(|due| Instant::now() >= due) {
+            self.search_due = None;
+            match self.mode {
+                Mode::Grep => self.file_searcher.start_grep(&self.input, 200),
+                Mode::Plugin(i) => {
+                    let plugin = self.config.plugins[i].clone();
+                    let query = self.input.clone();
+                    self.plugin_search
+                        .start(move |cancel, tx| run_plugin(&plugin, &query, cancel, tx));
+                }
+                _ => self.file_searcher.start_search(&self.input, 50),
+            }
+        }
+
+        let arrived = if let Some(files) = self.file_searcher.poll_results() {
+            self.filtered_files = files;
+            true
+        } else if let Some(matches) = self.file_searcher.poll_grep_results() {
+            self.filtered_matches = matches;
+            true
+        } else if let Some(items) = self.plugin_search.poll() {
+            self.plugin_items = items;
+            true
+        } else {
+            false
+        };
+
+        if arrived && self.selected_index >= self.result_count() {
+            self.selected_index = 0;
+        }
+        arrived
+    }
+
+    // =========================================================================
+    // File Preview
+    // =========================================================================
+
+    pub fn preview_visible(&self) -> bool {
+        self.show_preview && self.mode == Mode::Files
+    }
+
+    pub fn toggle_preview(&mut self) {
+        self.show_preview = !self.show_preview;
+    }
+
+    /// Follow the selection with the preview. The previous file stays on screen
+    /// until the new one is read, so moving through results doesn't flicker.
+    /// Returns true if the preview changed.
+    pub fn poll_preview(&mut self) -> bool {
+        let wanted = if self.preview_visible() {
+            self.filtered_files.get(self.selected_index).cloned()
+        } else {
+            None
+        };
+
+        let mut changed = false;
+        if wanted != self.preview_path {
+            match &wanted {
+                Some(path) => {
+                    let path = path.clone();
+                    self.preview_job.start(move |_, tx| {
+                        let _ = tx.send(read_preview(&path));
+                    });
+                }
+                None => {
+                    self.preview_job.cancel();
+                    self.preview.clear();
+                    changed = true;
+                }
+            }
+            self.preview_path = wanted;
+        }
+        if let Some(lines) = self.preview_job.poll() {
+            self.preview = lines;
+            changed = true;
+        }
+        changed
+    }
+
     // =========================================================================
     // Input Handling
     // =========================================================================
 
+    /// Length of the query in graphemes, so a CJK character, an emoji or a
+    /// letter with combining accents is a single cursor step
+    fn input_len(&self) -> usize {
+        self.input.graphemes(true).count()
+    }
+
+    /// Byte offset in the query of grapheme `pos`
+    fn byte_index(&self, pos: usize) -> usize {
+        self.input.grapheme_indices(true).nth(pos).map_or(self.input.len(), |(i, _)| i)
+    }
+
+    /// The query up to the cursor, to place it on screen
+    pub fn input_before_cursor(&self) -> &str {
+        &self.input[..self.byte_index(self.cursor_pos)]
+    }
+
+    /// Remove the grapheme at `pos`
+    fn remove_grapheme(&mut self, pos: usize) {
+        let start = self.byte_index(pos);
+        let end = self.byte_index(pos + 1);
+        self.input.replace_range(start..end, "");
+    }
+
     pub fn insert_char(&mut self, c: char) {
-        self.input.insert(self.cursor_pos, c);
-        self.cursor_pos += 1;
+        let at = self.byte_index(self.cursor_pos);
+        self.input.insert(at, c);
+        // A combining mark joins the grapheme before it rather than adding one
+        self.cursor_pos = self.input[..at + c.len_utf8()].graphemes(true).count();
         self.update_search();
     }
 
     pub fn backspace(&mut self) {
         if self.cursor_pos > 0 {
             self.cursor_pos -= 1;
-            self.input.remove(self.cursor_pos);
+            self.remove_grapheme(self.cursor_pos);
             self.update_search();
+        } else if self.input.is_empty() {
+            // Backspace on an empty query walks up the tree in Browse mode
+            self.browse_up();
         }
     }
 
     pub fn delete_char(&mut self) {
-        if self.cursor_pos < self.input.len() {
-            self.input.remove(self.cursor_pos);
+        if self.cursor_pos < self.input_len() {
+            self.remove_grapheme(self.cursor_pos);
+            if self.input_mode == InputMode::Normal {
+                self.clamp_normal_cursor();
+            }
             self.update_search();
         }
     }
@@ -102,9 +506,12 @@ impl App {
     }
 
     pub fn move_cursor_right(&mut self) {
-        if self.cursor_pos < self.input.len() {
+        if self.cursor_pos < self.input_len() {
             self.cursor_pos += 1;
         }
+        if self.input_mode == InputMode::Normal {
+            self.clamp_normal_cursor();
+        }
     }
 
     pub fn move_cursor_start(&mut self) {
@@ -112,7 +519,10 @@ impl App {
     }
 
     pub fn move_cursor_end(&mut self) {
-        self.cursor_pos = self.input.len();
+        self.cursor_pos = self.input_len();
+        if self.input_mode == InputMode::Normal {
+            self.clamp_normal_cursor();
+        }
     }
 
     pub fn clear_input(&mut self) {
@@ -121,12 +531,143 @@ impl App {
         self.update_search();
     }
 
+    // =========================================================================
+    // Query History
+    // =========================================================================
+
+    /// Up/Down walk the query history while the input is empty or still
+    /// shows a recalled query; once it is edited they move the selection again
+    pub fn recalling_query(&self) -> bool {
+        self.input.is_empty() || self.query_pos.is_some_and(|i| self.query_history.get(i) == Some(&self.input))
+    }
+
+    /// Show the next older (or newer) past query of this mode. Stepping newer
+    /// than the newest clears the input. False if there was nothing to show.
+    pub fn recall_query(&mut self, older: bool) -> bool {
+        let current = self.query_pos.filter(|&i| self.query_history.get(i) == Some(&self.input));
+        let pos = match current {
+            None if older => {
+                self.query_history = self.db.recent_queries(&self.mode_name());
+                0
+            }
+            None => return false,
+            Some(i) if older => i + 1,
+            Some(0) => {
+                self.query_pos = None;
+                self.clear_input();
+                return true;
+            }
+            Some(i) => i - 1,
+        };
+        let Some(query) = self.query_history.get(pos).cloned() else {
+            return false;
+        };
+
+        self.query_pos = Some(pos);
+        self.input = query;
+        self.cursor_pos = self.input_len();
+        self.selected_index = 0;
+        self.update_search();
+        true
+    }
+
+    // =========================================================================
+    // Vim Editing - normal mode motions and operators on the query
+    // =========================================================================
+
+    /// Where `motion` takes the cursor
+    fn motion_target(&self, motion: Motion) -> usize {
+        let graphemes: Vec<&str> = self.input.graphemes(true).collect();
+        let pos = self.cursor_pos.min(graphemes.len());
+        match motion {
+            Motion::WordForward => word_forward(&graphemes, pos),
+            Motion::WordBackward => word_backward(&graphemes, pos),
+            Motion::WordEnd => word_end(&graphemes, pos),
+            Motion::LineStart => 0,
+            Motion::LineEnd => graphemes.len(),
+        }
+    }
+
+    pub fn move_cursor(&mut self, motion: Motion) {
+        self.cursor_pos = self.motion_target(motion);
+        self.clamp_normal_cursor();
+    }
+
+    /// In normal mode the cursor sits on a character, never past the last one
+    fn clamp_normal_cursor(&mut self) {
+        self.cursor_pos = self.cursor_pos.min(self.input_len().saturating_sub(1));
+    }
+
+    /// `a`: insert after the character under the cursor
+    pub fn append(&mut self) {
+        if !self.input.is_empty() {
+            self.cursor_pos = (self.cursor_pos + 1).min(self.input_len());
+        }
+        self.input_mode = InputMode::Insert;
+    }
+
+    pub fn start_operator(&mut self, operator: Operator) {
+        self.pending_operator = Some(PendingOperator { operator, inner: false });
+    }
+
+    /// `dw`, `cb`, `d$`...: from the cursor to where `motion` goes
+    pub fn operate(&mut self, operator: Operator, motion: Motion) {
+        let graphemes: Vec<&str> = self.input.graphemes(true).collect();
+        let pos = self.cursor_pos.min(graphemes.len());
+        let target = match (operator, motion) {
+            // As in vim, `cw` on a word changes up to its end and keeps the space after it
+            (Operator::Change, Motion::WordForward) if graphemes.get(pos).is_some_and(|g| char_class(g) != 0) => {
+                let class = char_class(graphemes[pos]);
+                graphemes[pos..].iter().position(|g| char_class(g) != class).map_or(graphemes.len(), |i| pos + i)
+            }
+            // `de` takes the last character of the word too
+            (_, Motion::WordEnd) => word_end(&graphemes, pos) + 1,
+            _ => self.motion_target(motion),
+        };
+        self.apply_operator(operator, pos.min(target), pos.max(target));
+    }
+
+    /// `diw` / `ciw`: the word (or run of blanks or punctuation) under the cursor
+    pub fn operate_inner_word(&mut self, operator: Operator) {
+        let graphemes: Vec<&str> = self.input.graphemes(true).collect();
+        let Some(pos) = graphemes.len().checked_sub(1).map(|last| self.cursor_pos.min(last)) else {
+            return;
+        };
+        let class = char_class(graphemes[pos]);
+        let start = graphemes[..pos].iter().rposition(|g| char_class(g) != class).map_or(0, |i| i + 1);
+        let end = graphemes[pos..].iter().position(|g| char_class(g) != class).map_or(graphemes.len(), |i| pos + i);
+        self.apply_operator(operator, start, end);
+    }
+
+    /// `dd` / `cc`: the whole query
+    pub fn operate_line(&mut self, operator: Operator) {
+        let len = self.input_len();
+        self.apply_operator(operator, 0, len);
+    }
+
+    /// Delete the graphemes in `start..end`; `Change` then continues in insert mode
+    fn apply_operator(&mut self, operator: Operator, start: usize, end: usize) {
+        let graphemes: Vec<&str> = self.input.graphemes(true).collect();
+        let end = end.min(graphemes.len());
+        let start = start.min(end);
+        self.input = graphemes[..start].concat() + &graphemes[end..].concat();
+        self.cursor_pos = start;
+        match operator {
+            Operator::Delete => self.clamp_normal_cursor(),
+            Operator::Change => self.input_mode = InputMode::Insert,
+        }
+        self.update_search();
+    }
+
     // =========================================================================
     // Mode Switching
     // =========================================================================
 
     pub fn enter_normal_mode(&mut self) {
         self.input_mode = InputMode::Normal;
+        // Like vim, leaving insert mode steps back onto the last typed character
+        self.cursor_pos = self.cursor_pos.saturating_sub(1);
+        self.clamp_normal_cursor();
     }
 
     pub fn enter_insert_mode(&mut self) {
@@ -142,12 +683,75 @@ impl App {
     }
 
     pub fn toggle_mode(&mut self) {
+        if self.open_with.is_some() {
+            self.cancel_open_with();
+            return;
+        }
         self.mode = match self.mode {
             Mode::Apps => Mode::Files,
-            Mode::Files => Mode::Apps,
+            Mode::Files => Mode::Grep,
+            Mode::Grep => Mode::Browse,
+            Mode::Browse => Mode::Zoxide,
+            Mode::Zoxide => Mode::Projects,
+            Mode::Projects => Mode::History,
+            // Plugins follow the built-in modes in config order
+            Mode::History if !self.config.plugins.is_empty() => Mode::Plugin(0),
+            Mode::Plugin(i) if i + 1 < self.config.plugins.len() => Mode::Plugin(i + 1),
+            Mode::History | Mode::Plugin(_) => Mode::Apps,
         };
         self.selected_index = 0;
+        self.marked.clear();
+        self.query_pos = None;
+
+        match self.mode {
+            Mode::Browse => self.browse_entries = list_directory(&self.browse_dir),
+            Mode::Zoxide => self.zoxide_dirs = zoxide_dirs(),
+            Mode::Projects if self.all_projects.is_empty() => {
+                self.all_projects = scan_projects(&self.config.projects);
+            }
+            Mode::History => self.history = self.db.launch_history(200),
+            // Another plugin's items would be misleading while this one runs
+            Mode::Plugin(_) => self.plugin_items.clear(),
+            _ => {}
+        }
+        self.update_search();
+    }
+
+    /// Short name of the current mode, as logged with launches
+    pub fn mode_name(&self) -> String {
+        match self.mode {
+            Mode::Apps => "apps".to_string(),
+            Mode::Files => "files".to_string(),
+            Mode::Grep => "grep".to_string(),
+            Mode::Browse => "browse".to_string(),
+            Mode::Zoxide => "zoxide".to_string(),
+            Mode::Projects => "projects".to_string(),
+            Mode::History => "history".to_string(),
+            Mode::Plugin(i) => format!("plugin:{}", self.config.plugins[i].name),
+        }
+    }
+
+    /// Nudge the selected app's base score by `delta` and re-rank right away,
+    /// keeping the app selected
+    pub fn adjust_score(&mut self, delta: i32) {
+        if self.mode != Mode::Apps {
+            return;
+        }
+        let Some(name) = self.filtered_apps.get(self.selected_index).map(|app| app.name.clone()) else {
+            return;
+        };
+        let Ok(base) = self.db.adjust_base_score(&name, delta) else {
+            return;
+        };
+
+        for app in self.all_apps.iter_mut().filter(|app| app.name == name) {
+            app.total_score += delta;
+        }
+        self.all_apps.sort_by(|a, b| b.total_score.cmp(&a.total_score).then_with(|| a.name.cmp(&b.name)));
+        self.app_matches.clear();
         self.update_search();
+        self.selected_index = self.filtered_apps.iter().position(|app| app.name == name).unwrap_or(0);
+        self.flash = Some((format!("{} base score {}", name, base), Instant::now()));
     }
 
     pub fn toggle_dormant(&mut self) {
@@ -195,38 +799,419 @@ impl App {
     fn update_search(&mut self) {
         self.selected_index = 0;
 
+        // Leaving a mode abandons whatever search it has pending or still running
+        if !matches!(self.mode, Mode::Files | Mode::Grep | Mode::Plugin(_)) {
+            self.search_due = None;
+        }
+        if !matches!(self.mode, Mode::Files | Mode::Grep) {
+            self.file_searcher.cancel();
+        }
+        if !matches!(self.mode, Mode::Plugin(_)) {
+            self.plugin_search.cancel();
+        }
+
         match self.mode {
             Mode::Apps => {
-                let matched = if self.input.is_empty() {
-                    self.all_apps.clone()
+                // "@dev fire" restricts to Development apps, then fuzzy-matches "fire";
+                // anything after "--" is arguments, not part of the query
+                let (query, _) = split_launch_args(&self.input);
+                let (query, categories) = split_category_filters(&query);
+
+                // Desktop actions only surface once the query asks for them
+                let matched: Vec<AppEntry> = if query.is_empty() {
+                    self.all_apps
+                        .iter()
+                        .filter(|app| !app.is_action && categories.iter().all(|c| matches_category(app, c)))
+                        .cloned()
+                        .collect()
                 } else {
-                    fuzzy_search_apps(&self.input, &self.all_apps)
+                    search_apps(&mut self.app_matches, &query, &categories, &self.all_apps)
                         .into_iter()
                         .cloned()
                         .collect()
                 };
 
+                let before = matched.len();
                 self.filtered_apps = matched
                     .into_iter()
                     .filter(|app| self.show_dormant || !app.is_dormant)
                     .collect();
+                self.hidden_dormant = before - self.filtered_apps.len();
             }
             Mode::Files => {
-                // Streaming file search - only search when there's a query
-                if self.input.is_empty() {
-                    self.filtered_files.clear();
+                // Runs on a worker thread; each keystroke cancels the previous search
+                // and the old results stay visible until the new ones arrive.
+                // Rapid typing is coalesced: the search starts in poll_search.
+                self.schedule_search();
+            }
+            Mode::Grep => {
+                // Same worker and debounce as Files, grepping contents instead
+                self.schedule_search();
+            }
+            Mode::Browse => {
+                // Dotfiles stay hidden until the user starts filtering
+                self.filtered_entries = if self.input.is_empty() {
+                    self.browse_entries
+                        .iter()
+                        .filter(|e| !e.name.starts_with('.'))
+                        .cloned()
+                        .collect()
                 } else {
-                    // This is fast because it streams results and stops early
-                    self.filtered_files = self.file_searcher.search(&self.input, 50);
-                }
+                    fuzzy_search_entries(&self.input, &self.browse_entries)
+                        .into_iter()
+                        .cloned()
+                        .collect()
+                };
+            }
+            Mode::Zoxide => {
+                self.filtered_dirs = if self.input.is_empty() {
+                    self.zoxide_dirs.iter().take(50).cloned().collect()
+                } else {
+                    fuzzy_search_paths(&self.input, &self.zoxide_dirs, 50)
+                };
             }
+            Mode::Projects => {
+                self.filtered_dirs = if self.input.is_empty() {
+                    self.all_projects.iter().take(50).cloned().collect()
+                } else {
+                    fuzzy_search_paths(&self.input, &self.all_projects, 50)
+                };
+            }
+            Mode::History => {
+                self.filtered_history = fuzzy_search_history(&self.input, &self.history)
+                    .into_iter()
+                    .cloned()
+                    .collect();
+            }
+            Mode::Plugin(_) => {
+                // Plugins do their own filtering, and may list items for an empty query
+                self.plugin_search.cancel();
+                let debounce = Duration::from_millis(self.config.files.debounce_ms);
+                self.search_due = Some(Instant::now() + debounce);
+            }
+        }
+    }
+
+    /// Cancel the running search and start a new one once typing pauses
+    fn schedule_search(&mut self) {
+        self.file_searcher.cancel();
+        if self.input.is_empty() {
+            self.search_due = None;
+            self.filtered_files.clear();
+            self.filtered_matches.clear();
+        } else {
+            let debounce = Duration::from_millis(self.config.files.debounce_ms);
+            self.search_due = Some(Instant::now() + debounce);
         }
     }
 
-    fn result_count(&self) -> usize {
+    pub fn result_count(&self) -> usize {
         match self.mode {
             Mode::Apps => self.filtered_apps.len(),
             Mode::Files => self.filtered_files.len(),
+            Mode::Grep => self.filtered_matches.len(),
+            Mode::Browse => self.filtered_entries.len(),
+            Mode::Zoxide | Mode::Projects => self.filtered_dirs.len(),
+            Mode::History => self.filtered_history.len(),
+            Mode::Plugin(_) => self.plugin_items.len(),
+        }
+    }
+
+    /// Everything the current mode searches through, where that is known
+    /// up front (Files, Grep and plugins stream their results)
+    pub fn candidate_count(&self) -> Option<usize> {
+        match self.mode {
+            Mode::Apps => Some(self.all_apps.iter().filter(|app| !app.is_action).count()),
+            Mode::Browse => Some(self.browse_entries.len()),
+            Mode::Zoxide => Some(self.zoxide_dirs.len()),
+            Mode::Projects => Some(self.all_projects.len()),
+            Mode::History => Some(self.history.len()),
+            Mode::Files | Mode::Grep | Mode::Plugin(_) => None,
+        }
+    }
+
+    // =========================================================================
+    // App Details
+    // =========================================================================
+
+    /// Open the details panel for the selected app
+    pub fn show_app_details(&mut self) {
+        if self.mode != Mode::Apps {
+            return;
+        }
+        let Some(app) = self.filtered_apps.get(self.selected_index) else {
+            return;
+        };
+
+        let (_, seed_score, usage, last_used) = self.db.get_app_data(&app.name);
+        let frecency = self.db.frecency(&app.name);
+        self.app_details = Some(AppDetails {
+            name: app.name.clone(),
+            exec: shell_words::join(app.exec_args(&[])),
+            desktop_path: app.desktop_path.clone(),
+            categories: app.categories.clone(),
+            usage,
+            last_used,
+            is_tui: self.is_tui(app),
+            tui_stored: self.db.has_entry(&app.name),
+            seed_score,
+            frecency,
+            total_score: app.total_score,
+            is_dormant: app.is_dormant,
+        });
+    }
+
+    pub fn close_app_details(&mut self) {
+        self.app_details = None;
+    }
+
+    // =========================================================================
+    // Marks (multi-select)
+    // =========================================================================
+
+    /// Stable identity of a result, so marks survive a changing query
+    fn mark_key(&self, i: usize) -> Option<String> {
+        match self.mode {
+            Mode::Apps => self.filtered_apps.get(i).map(|app| app.name.clone()),
+            Mode::Files => self.filtered_files.get(i).cloned(),
+            // A marked match stands for its whole file
+            Mode::Grep => self.filtered_matches.get(i).map(|m| m.path.clone()),
+            Mode::Browse => self.filtered_entries.get(i).map(|e| e.path.clone()),
+            Mode::Zoxide | Mode::Projects => self.filtered_dirs.get(i).cloned(),
+            Mode::History => self.filtered_history.get(i).map(|entry| entry.command_line()),
+            Mode::Plugin(_) => self.plugin_items.get(i).map(|item| item.title.clone()),
+        }
+    }
+
+    pub fn is_marked(&self, i: usize) -> bool {
+        !self.marked.is_empty() && self.mark_key(i).is_some_and(|key| self.marked.contains(&key))
+    }
+
+    /// Mark or unmark the selection and move on to the next result
+    pub fn toggle_mark(&mut self) {
+        let Some(key) = self.mark_key(self.selected_index) else {
+            return;
+        };
+        if let Some(pos) = self.marked.iter().position(|k| *k == key) {
+            self.marked.remove(pos);
+        } else {
+            self.marked.push(key);
+        }
+        if self.selected_index + 1 < self.result_count() {
+            self.selected_index += 1;
+        }
+    }
+
+    /// Launch every marked result; text files share a single editor
+    fn launch_marked(&mut self) {
+        let marked = std::mem::take(&mut self.marked);
+
+        match self.mode {
+            Mode::Files | Mode::Grep | Mode::Browse => {
+                let files: Vec<String> = marked.into_iter().filter(|p| !PathBuf::from(p).is_dir()).collect();
+                let (text, other): (Vec<String>, Vec<String>) = files.into_iter().partition(|p| is_text_file(p));
+                if !text.is_empty() {
+                    let mut args = vec!["-e".to_string(), "nvim".to_string()];
+                    args.extend(text);
+                    self.launch_command = Some(("kitty".to_string(), args, false));
+                    self.queue_launch();
+                }
+                for path in other {
+                    self.open_with_default(path);
+                    self.queue_launch();
+                }
+            }
+            Mode::Apps => {
+                for name in marked {
+                    if let Some(app) = self.all_apps.iter().find(|app| app.name == name).cloned() {
+                        self.launch_app(&app, &[], &[]);
+                        self.queue_launch();
+                    }
+                }
+            }
+            Mode::Zoxide | Mode::Projects => {
+                for dir in marked {
+                    self.open_directory_path(dir, false);
+                    self.queue_launch();
+                }
+            }
+            Mode::History => {
+                for key in marked {
+                    if let Some(entry) = self.history.iter().find(|entry| entry.command_line() == key) {
+                        self.launch_command = Some((entry.program.clone(), entry.args.clone(), entry.is_tui));
+                        self.queue_launch();
+                    }
+                }
+            }
+            Mode::Plugin(i) => {
+                for title in marked {
+                    if let Some(item) = self.plugin_items.iter().find(|item| item.title == title).cloned() {
+                        self.launch_plugin_item(i, &item);
+                        self.queue_launch();
+                    }
+                }
+            }
+        }
+        self.should_launch = !self.launch_batch.is_empty();
+    }
+
+    /// Move the pending launch into the batch
+    fn queue_launch(&mut self) {
+        if let Some(command) = self.launch_command.take() {
+            self.launch_batch.push((command, self.launched_app.take()));
+        }
+    }
+
+    /// Everything to spawn for this launch: the marked batch, or the single selection
+    pub fn take_launches(&mut self) -> Vec<(LaunchCommand, Option<String>)> {
+        self.queue_launch();
+        std::mem::take(&mut self.launch_batch)
+    }
+
+    // =========================================================================
+    // Clipboard
+    // =========================================================================
+
+    /// Copy the selected path, directory or command to the clipboard
+    pub fn yank_selection(&mut self) {
+        // Marked paths are copied one per line
+        if !self.marked.is_empty()
+            && matches!(self.mode, Mode::Files | Mode::Grep | Mode::Browse | Mode::Zoxide | Mode::Projects)
+        {
+            let text = self.marked.join("\n");
+            self.copy(&text);
+            return;
+        }
+
+        let i = self.selected_index;
+        let text = match self.mode {
+            Mode::Apps => self.filtered_apps.get(i).map(|app| shell_words::join(app.exec_args(&[]))),
+            Mode::Files | Mode::Grep => self.selected_file(),
+            // Directories too, unlike the file actions
+            Mode::Browse => self.filtered_entries.get(i).map(|e| e.path.clone()),
+            Mode::Zoxide | Mode::Projects => self.filtered_dirs.get(i).cloned(),
+            Mode::History => self.filtered_history.get(i).map(|entry| entry.command_line()),
+            Mode::Plugin(_) => self.plugin_items.get(i).map(|item| {
+                if item.exec.is_empty() { item.title.clone() } else { item.exec.clone() }
+            }),
+        };
+        if let Some(text) = text {
+            self.copy(&text);
+        }
+    }
+
+    fn copy(&mut self, text: &str) {
+        if copy_to_clipboard(text) {
+            self.flash = Some((format!("Copied {}", text), Instant::now()));
+        } else {
+            self.launch_error = Some("No clipboard tool found (wl-copy, xclip or xsel)".to_string());
+        }
+    }
+
+    /// Time left before the flash disappears
+    pub fn flash_remaining(&self) -> Option<Duration> {
+        self.flash.as_ref().map(|(_, shown)| FLASH_DURATION.saturating_sub(shown.elapsed()))
+    }
+
+    /// Drop the flash once it has been up long enough; true if it went away
+    pub fn expire_flash(&mut self) -> bool {
+        if self.flash_remaining().is_some_and(|left| left.is_zero()) {
+            self.flash = None;
+            return true;
+        }
+        false
+    }
+
+    // =========================================================================
+    // File Action Menu
+    // =========================================================================
+
+    /// Path of the selected file in Files, Grep or Browse mode
+    fn selected_file(&self) -> Option<String> {
+        match self.mode {
+            Mode::Files => self.filtered_files.get(self.selected_index).cloned(),
+            Mode::Grep => self.filtered_matches.get(self.selected_index).map(|m| m.path.clone()),
+            Mode::Browse => self
+                .filtered_entries
+                .get(self.selected_index)
+                .filter(|e| !e.is_dir)
+                .map(|e| e.path.clone()),
+            _ => None,
+        }
+    }
+
+    pub fn open_file_menu(&mut self) {
+        if let Some(path) = self.selected_file() {
+            self.file_menu = Some(FileMenu { path, selected: 0 });
+        }
+    }
+
+    pub fn close_file_menu(&mut self) {
+        self.file_menu = None;
+    }
+
+    pub fn file_menu_next(&mut self) {
+        if let Some(menu) = &mut self.file_menu {
+            menu.selected = (menu.selected + 1) % FileAction::ALL.len();
+        }
+    }
+
+    pub fn file_menu_previous(&mut self) {
+        if let Some(menu) = &mut self.file_menu {
+            menu.selected = menu.selected.checked_sub(1).unwrap_or(FileAction::ALL.len() - 1);
+        }
+    }
+
+    /// Run `action`, or the highlighted one, on the menu's file and close the menu
+    pub fn run_file_action(&mut self, action: Option<FileAction>) {
+        let Some(menu) = self.file_menu.take() else {
+            return;
+        };
+        let path = menu.path;
+
+        match action.unwrap_or(FileAction::ALL[menu.selected]) {
+            FileAction::Editor => self.open_in_editor(path, None),
+            FileAction::Default => self.open_with_default(path),
+            FileAction::OpenWith => self.pick_app_for(path),
+            FileAction::CopyPath => self.copy(&path),
+            FileAction::Reveal => {
+                let (program, args) = reveal_command(&path);
+                self.launch_command = Some((program, args, false));
+                self.should_launch = true;
+            }
+            FileAction::Trash => match move_to_trash(&path) {
+                Ok(()) => {
+                    self.filtered_files.retain(|p| *p != path);
+                    self.filtered_matches.retain(|m| m.path != path);
+                    self.filtered_entries.retain(|e| e.path != path);
+                    self.browse_entries.retain(|e| e.path != path);
+                    self.selected_index = self.selected_index.min(self.result_count().saturating_sub(1));
+                }
+                Err(e) => self.launch_error = Some(format!("Failed to trash {}: {}", path, e)),
+            },
+        }
+    }
+
+    // =========================================================================
+    // Directory Browsing
+    // =========================================================================
+
+    fn browse_into(&mut self, dir: PathBuf) {
+        self.browse_dir = dir;
+        self.browse_entries = list_directory(&self.browse_dir);
+        self.input.clear();
+        self.cursor_pos = 0;
+        self.update_search();
+    }
+
+    /// Go to the parent of the current browse directory
+    pub fn browse_up(&mut self) {
+        if self.mode != Mode::Browse {
+            return;
+        }
+        if let Some(parent) = self.browse_dir.parent() {
+            let parent = parent.to_path_buf();
+            self.browse_into(parent);
         }
     }
 
@@ -234,73 +1219,330 @@ impl App {
     // Actions
     // =========================================================================
 
+    /// Stored preference if the user has one, otherwise the entry's own default
+    pub fn is_tui(&self, app: &AppEntry) -> bool {
+        if self.db.has_entry(&app.name) {
+            self.db.is_tui_app(&app.name)
+        } else {
+            app.default_tui()
+        }
+    }
+
     pub fn toggle_tui_preference(&mut self) -> bool {
         if let Mode::Apps = self.mode {
             if self.filtered_apps.is_empty() {
                 return false;
             }
             let app = &self.filtered_apps[self.selected_index];
-            let current_state = self.db.is_tui_app(&app.name);
-            let _ = self.db.set_tui_mode(&app.name, !current_state);
+            let current_state = self.is_tui(app);
+            if let Err(e) = self.db.set_tui_mode(&app.name, !current_state) {
+                tracing::warn!("saving terminal preference of {} failed: {}", app.name, e);
+            }
             return true;
         }
         false
     }
 
     pub fn launch_selection(&mut self) {
+        if !self.marked.is_empty() {
+            self.launch_marked();
+            return;
+        }
+
         match self.mode {
             Mode::Apps => {
                 if self.filtered_apps.is_empty() {
                     return;
                 }
-                let app = &self.filtered_apps[self.selected_index];
-
-                // Update usage stats
-                let _ = self.db.increment_usage(&app.name);
+                let app = self.filtered_apps[self.selected_index].clone();
+                let (_, extra_args) = split_launch_args(&self.input);
+                let files: Vec<String> = self.open_with.take().map(|o| o.path).into_iter().collect();
+                self.launch_app(&app, &files, &extra_args);
+            }
+            Mode::Files => {
+                if self.filtered_files.is_empty() {
+                    return;
+                }
+                let file_path = self.filtered_files[self.selected_index].clone();
+                self.open_file(file_path);
+            }
+            Mode::Grep => {
+                if self.filtered_matches.is_empty() {
+                    return;
+                }
+                let hit = self.filtered_matches[self.selected_index].clone();
+                self.open_in_editor(hit.path, Some(hit.line));
+            }
+            Mode::Browse => {
+                if self.filtered_entries.is_empty() {
+                    return;
+                }
+                let entry = self.filtered_entries[self.selected_index].clone();
 
-                // Determine if TUI
-                let is_tui = if self.db.has_entry(&app.name) {
-                    self.db.is_tui_app(&app.name)
+                if entry.is_dir {
+                    self.browse_into(PathBuf::from(entry.path));
                 } else {
-                    app.is_cli_only
-                };
-
-                // Parse exec command
-                let clean_exec = app
-                    .exec
-                    .split_whitespace()
-                    .filter(|s| !s.starts_with('%'))
-                    .collect::<Vec<&str>>()
-                    .join(" ");
-
-                let args_owned = shell_words::split(&clean_exec).unwrap_or_default();
-                if args_owned.is_empty() {
+                    self.open_file(entry.path);
+                }
+            }
+            Mode::Zoxide | Mode::Projects => self.open_directory(false),
+            Mode::History => {
+                if self.filtered_history.is_empty() {
                     return;
                 }
-
-                let program = args_owned[0].clone();
-                let args: Vec<String> = args_owned[1..].iter().cloned().collect();
-
-                self.launch_command = Some((program, args, is_tui));
+                // Re-run exactly what was spawned, extra arguments included
+                let entry = self.filtered_history[self.selected_index].clone();
+                self.launch_command = Some((entry.program, entry.args, entry.is_tui));
                 self.should_launch = true;
             }
-            Mode::Files => {
-                if self.filtered_files.is_empty() {
+            Mode::Plugin(i) => {
+                if self.plugin_items.is_empty() {
                     return;
                 }
-                let file_path = self.filtered_files[self.selected_index].clone();
+                let item = self.plugin_items[self.selected_index].clone();
+                self.launch_plugin_item(i, &item);
+            }
+        }
+    }
 
-                self.launch_command = Some((
-                    "kitty".to_string(),
-                    vec!["-e".to_string(), "nvim".to_string(), file_path],
-                    false,
-                ));
-                self.should_launch = true;
+    /// Launch the selection but keep rula open for the next one
+    pub fn launch_selection_and_stay(&mut self) {
+        self.launch_selection();
+        self.stay_after_launch = self.should_launch;
+    }
+
+    /// Launch the selected app or history entry through `launch.root_command`
+    pub fn launch_selection_as_root(&mut self) {
+        if !matches!(self.mode, Mode::Apps | Mode::History) {
+            return;
+        }
+
+        let wrapper = match shell_words::split(&self.config.launch.root_command) {
+            Ok(words) if !words.is_empty() => words,
+            _ => return,
+        };
+
+        // sudo and doas prompt for the password on the terminal
+        let needs_terminal = matches!(wrapper[0].as_str(), "sudo" | "doas");
+        let wrap = |(program, args, is_tui): LaunchCommand| {
+            let mut wrapped = wrapper[1..].to_vec();
+            wrapped.push(program);
+            wrapped.extend(args);
+            (wrapper[0].clone(), wrapped, is_tui || needs_terminal)
+        };
+
+        self.launch_selection();
+        self.launch_command = self.launch_command.take().map(wrap);
+        for (command, _) in &mut self.launch_batch {
+            *command = wrap(std::mem::take(command));
+        }
+    }
+
+    /// Run the item's `exec`, or hand its `action` back to the plugin
+    fn launch_plugin_item(&mut self, plugin: usize, item: &PluginItem) {
+        let command = if !item.exec.is_empty() {
+            shell_words::split(&item.exec).ok().and_then(|words| {
+                let (program, args) = words.split_first()?;
+                Some((program.clone(), args.to_vec(), item.terminal))
+            })
+        } else if !item.action.is_empty() {
+            plugin_command(&self.config.plugins[plugin]).map(|(program, mut args)| {
+                args.extend(["--action".to_string(), item.action.clone()]);
+                (program, args, false)
+            })
+        } else {
+            None
+        };
+
+        if let Some(command) = command {
+            self.launch_command = Some(command);
+            self.should_launch = true;
+        }
+    }
+
+    /// Launch an app entry, handing `files` to its %f/%F/%u/%U field codes and
+    /// appending `extra_args` to the expanded command
+    fn launch_app(&mut self, app: &AppEntry, files: &[String], extra_args: &[String]) {
+        // Determine if TUI
+        let is_tui = self.is_tui(app);
+
+        // Update usage stats (first launch records the resolved TUI default)
+        if !self.private {
+            if let Err(e) = self.db.increment_usage(&app.name, is_tui) {
+                tracing::warn!("recording usage of {} failed: {}", app.name, e);
             }
         }
+
+        // Parse exec command, expanding field codes
+        let args_owned = app.exec_args(files);
+        if args_owned.is_empty() {
+            return;
+        }
+
+        let program = args_owned[0].clone();
+        let mut args: Vec<String> = args_owned[1..].to_vec();
+        args.extend(extra_args.iter().cloned());
+
+        self.launch_command = Some((program, args, is_tui));
+        self.launched_app = Some(app.name.clone());
+        self.should_launch = true;
+    }
+
+    /// Open the selected directory in the editor instead of a plain shell, or
+    /// the selected file in the editor whatever its type
+    pub fn launch_selection_in_editor(&mut self) {
+        if matches!(self.mode, Mode::Zoxide | Mode::Projects) {
+            self.open_directory(true);
+        } else if self.mode == Mode::Grep {
+            self.launch_selection();
+        } else if let Some(path) = self.selected_file() {
+            self.open_in_editor(path, None);
+        }
+    }
+
+    fn open_directory(&mut self, in_editor: bool) {
+        if self.filtered_dirs.is_empty() {
+            return;
+        }
+        let dir = self.filtered_dirs[self.selected_index].clone();
+        self.open_directory_path(dir, in_editor);
+    }
+
+    fn open_directory_path(&mut self, dir: String, in_editor: bool) {
+        // Feed the jump back into zoxide so its frecency stays accurate
+        if self.mode == Mode::Zoxide && !self.private {
+            zoxide_add(&dir);
+        }
+
+        let mut args = vec!["--directory".to_string(), dir];
+        if in_editor {
+            args.extend(["-e".to_string(), "nvim".to_string(), ".".to_string()]);
+        }
+        self.launch_command = Some(("kitty".to_string(), args, false));
+        self.should_launch = true;
+    }
+
+    /// Text files go to the editor, everything else to its default application
+    fn open_file(&mut self, file_path: String) {
+        if is_text_file(&file_path) {
+            self.open_in_editor(file_path, None);
+        } else {
+            self.open_with_default(file_path);
+        }
+    }
+
+    fn open_with_default(&mut self, file_path: String) {
+        self.launch_command = Some(("xdg-open".to_string(), vec![file_path], false));
+        self.should_launch = true;
+    }
+
+    /// Start the "Open with" picker for the selected file
+    pub fn open_selection_with(&mut self) {
+        if let Some(path) = self.selected_file() {
+            self.pick_app_for(path);
+        }
+    }
+
+    /// Switch to the app list; the next app launched gets `path` as its file
+    fn pick_app_for(&mut self, path: String) {
+        self.open_with = Some(OpenWith {
+            path,
+            return_mode: self.mode,
+            return_input: std::mem::take(&mut self.input),
+            return_index: self.selected_index,
+        });
+        self.mode = Mode::Apps;
+        self.marked.clear();
+        self.cursor_pos = 0;
+        self.input_mode = InputMode::Insert;
+        self.update_search();
+    }
+
+    /// Leave the "Open with" picker for the file view it was started from
+    pub fn cancel_open_with(&mut self) {
+        if let Some(open_with) = self.open_with.take() {
+            // The old results are still there, so no new search is needed
+            self.mode = open_with.return_mode;
+            self.input = open_with.return_input;
+            self.cursor_pos = self.input_len();
+            self.selected_index = open_with.return_index.min(self.result_count().saturating_sub(1));
+        }
+    }
+
+    /// Open a file in the editor, optionally jumping to a 1-based line
+    fn open_in_editor(&mut self, file_path: String, line: Option<usize>) {
+        let mut args = vec!["-e".to_string(), "nvim".to_string()];
+        if let Some(line) = line {
+            args.push(format!("+{}", line));
+        }
+        args.push(file_path);
+
+        self.launch_command = Some(("kitty".to_string(), args, false));
+        self.should_launch = true;
     }
 
     pub fn quit(&mut self) {
         self.should_quit = true;
     }
 }
+
+/// Vim's character classes for word motions: blanks, keyword characters and
+/// other punctuation. A word is a run of one class; a grapheme takes the
+/// class of its base character.
+fn char_class(grapheme: &str) -> u8 {
+    let c = grapheme.chars().next().unwrap_or(' ');
+    if c.is_whitespace() {
+        0
+    } else if c.is_alphanumeric() || c == '_' {
+        1
+    } else {
+        2
+    }
+}
+
+/// `w`: start of the next word, or the end of the query
+fn word_forward(graphemes: &[&str], pos: usize) -> usize {
+    let mut i = pos;
+    if let Some(g) = graphemes.get(i) {
+        let class = char_class(g);
+        while i < graphemes.len() && char_class(graphemes[i]) == class {
+            i += 1;
+        }
+    }
+    while i < graphemes.len() && char_class(graphemes[i]) == 0 {
+        i += 1;
+    }
+    i
+}
+
+/// `b`: start of the current or previous word
+fn word_backward(graphemes: &[&str], pos: usize) -> usize {
+    let mut i = pos.min(graphemes.len());
+    while i > 0 && char_class(graphemes[i - 1]) == 0 {
+        i -= 1;
+    }
+    if i == 0 {
+        return 0;
+    }
+    let class = char_class(graphemes[i - 1]);
+    while i > 0 && char_class(graphemes[i - 1]) == class {
+        i -= 1;
+    }
+    i
+}
+
+/// `e`: last character of the current or next word
+fn word_end(graphemes: &[&str], pos: usize) -> usize {
+    let mut i = pos + 1;
+    while i < graphemes.len() && char_class(graphemes[i]) == 0 {
+        i += 1;
+    }
+    if i >= graphemes.len() {
+        return graphemes.len().saturating_sub(1);
+    }
+    let class = char_class(graphemes[i]);
+    while i + 1 < graphemes.len() && char_class(graphemes[i + 1]) == class {
+        i += 1;
+    }
+    i
+}
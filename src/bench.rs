@@ -0,0 +1,152 @@
+// ============================================================================
+// Benchmarks - `rula --bench` timing report
+// ============================================================================
+
+use std::thread;
+use std::time::{Duration, Instant};
+use crate::config::Config;
+use crate::db::Database;
+use crate::index::{FileIndex, SearchScope};
+use crate::report::Report;
+use crate::system::{fuzzy_search_apps, load_cached_apps, scan_apps_fresh, search_apps, FileSearcher, MatchCache};
+use crate::theme::*;
+
+/// Repeats of each warm measurement; the median is reported
+const RUNS: usize = 5;
+/// Repeats of each fuzzy query, enough to average out thread pool wake-ups
+const MATCH_RUNS: usize = 20;
+/// Prefix lengths of the sample query, from one key press to a full word
+const QUERY_LENGTHS: [usize; 4] = [1, 2, 4, 8];
+/// Typed in Files mode; the same limit the launcher asks for
+const FILE_QUERY: &str = "settings";
+const FILE_LIMIT: usize = 50;
+
+/// Time app scans, cache loads, fuzzy matching and file search on this machine
+pub fn run(db: &Database, config: &Config) {
+    let report = Report::new(config);
+    let theme = &report.theme;
+    let row = |label: &str, value: String| {
+        println!("  {}  {}", report.paint(Style::new().fg(theme.subtle), &format!("{:<20}", label)), value);
+    };
+
+    // The first scan reads desktop files from disk, later ones hit the page cache
+    report.heading("App scan");
+    let started = Instant::now();
+    let apps = scan_apps_fresh(db, &config.apps);
+    let cold = started.elapsed();
+    let warm = median((0..RUNS).map(|_| time(|| scan_apps_fresh(db, &config.apps))).collect());
+    row("apps", apps.len().to_string());
+    row("cold", format_duration(cold));
+    row(&format!("warm (median of {})", RUNS), format_duration(warm));
+
+    report.heading("App cache load");
+    let cached = load_cached_apps(db, &config.apps).len();
+    if cached == 0 {
+        row("cache", report.paint(Style::new().fg(theme.muted), "empty - run rula --rebuild-cache"));
+    } else {
+        let load = median((0..RUNS).map(|_| time(|| load_cached_apps(db, &config.apps))).collect());
+        row("apps", cached.to_string());
+        row(&format!("load (median of {})", RUNS), format_duration(load));
+    }
+
+    // Prefixes of a real app name, so every length has matches to rank
+    report.heading("Fuzzy match");
+    let sample = apps
+        .iter()
+        .map(|a| a.name.to_lowercase())
+        .find(|name| name.chars().count() >= 8 && name.chars().all(char::is_alphanumeric))
+        .unwrap_or_else(|| "terminal".to_string());
+    for len in QUERY_LENGTHS {
+        let query: String = sample.chars().take(len).collect();
+        let total = time(|| {
+            for _ in 0..MATCH_RUNS {
+                fuzzy_search_apps(&query, &apps);
+            }
+        });
+        let per_query = total / MATCH_RUNS as u32;
+        let throughput = (apps.len() * MATCH_RUNS) as f64 / total.as_secs_f64().max(f64::EPSILON);
+        row(
+            &format!("{:?}", query),
+            format!("{} per query, {:.0}k apps/s", format_duration(per_query), throughput / 1000.0),
+        );
+    }
+    // As the launcher sees it: each key press re-scores only what the last one matched
+    let typed_len = QUERY_LENGTHS[QUERY_LENGTHS.len() - 1];
+    let typed = time(|| {
+        for _ in 0..MATCH_RUNS {
+            let mut cache = MatchCache::default();
+            for len in 1..=typed_len {
+                let query: String = sample.chars().take(len).collect();
+                search_apps(&mut cache, &query, &[], &apps);
+            }
+        }
+    });
+    row(
+        "typed key by key",
+        format!("{} per key press", format_duration(typed / (MATCH_RUNS * typed_len) as u32)),
+    );
+
+    // Through the launcher's own searcher: first batch, then the final list
+    report.heading("File search");
+    let indexed = FileIndex::load().map(|index| index.paths().len());
+    row(
+        "index",
+        match indexed {
+            Ok(files) => format!("{} files", files),
+            Err(_) => report.paint(Style::new().fg(theme.muted), "none - live walk (run rula --index)"),
+        },
+    );
+    let mut searcher = FileSearcher::new(SearchScope::from_config(&config.files));
+    for len in QUERY_LENGTHS {
+        let query: String = FILE_QUERY.chars().take(len).collect();
+        let (first, done, results) = time_file_search(&mut searcher, &query);
+        row(
+            &format!("{:?}", query),
+            format!(
+                "first {}, done {}, {} results",
+                first.map(format_duration).unwrap_or_else(|| "-".to_string()),
+                format_duration(done),
+                results,
+            ),
+        );
+    }
+    println!();
+}
+
+/// Time to the first published results (if any) and to the finished search
+fn time_file_search(searcher: &mut FileSearcher, query: &str) -> (Option<Duration>, Duration, usize) {
+    let started = Instant::now();
+    searcher.start_search(query, FILE_LIMIT);
+    let mut first = None;
+    let mut results = 0;
+    loop {
+        if let Some(found) = searcher.poll_results() {
+            first.get_or_insert_with(|| started.elapsed());
+            results = found.len();
+        }
+        if !searcher.is_searching() {
+            break;
+        }
+        thread::sleep(Duration::from_millis(1));
+    }
+    (first, started.elapsed(), results)
+}
+
+fn time<T>(work: impl FnOnce() -> T) -> Duration {
+    let started = Instant::now();
+    let _ = work();
+    started.elapsed()
+}
+
+fn median(mut samples: Vec<Duration>) -> Duration {
+    samples.sort();
+    samples.get(samples.len() / 2).copied().unwrap_or_default()
+}
+
+fn format_duration(d: Duration) -> String {
+    if d < Duration::from_millis(1) {
+        format!("{} µs", d.as_micros())
+    } else {
+        format!("{:.1} ms", d.as_secs_f64() * 1000.0)
+    }
+}
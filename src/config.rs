@@ -0,0 +1,244 @@
+// ============================================================================
+// Configuration - User settings loaded from ~/.config/rula/config.toml
+// ============================================================================
+
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::PathBuf;
+use crate::error::Result;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub apps: AppsConfig,
+    pub projects: ProjectsConfig,
+    pub files: FilesConfig,
+    pub launch: LaunchConfig,
+    /// Icon overrides keyed by app name, `Icon=` name, or desktop category
+    pub icons: HashMap<String, String>,
+    /// Keyword -> command shortcuts shown as entries in Apps mode
+    pub aliases: HashMap<String, String>,
+    /// Extra launchable items for scripts and bookmarks, merged into Apps mode
+    pub entries: Vec<CustomEntry>,
+    /// External executables, each shown as its own mode after History
+    pub plugins: Vec<PluginConfig>,
+    /// Built-in palette, e.g. "rose-pine-dawn" (see Theme::BUILTIN)
+    pub theme: String,
+    /// Key bindings per input mode, on top of the defaults
+    pub keys: KeysConfig,
+    /// Keep the terminal's background (for transparency and blur) instead of
+    /// painting `base`; faint colors are lifted to stay readable
+    pub transparent: bool,
+    /// Palette overrides keyed by role ("love", "foam", "base", ...), as `#rrggbb`
+    pub colors: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct LaunchConfig {
+    /// Prefix for elevated launches (Ctrl+Enter), e.g. "pkexec" or "sudo -E"
+    pub root_command: String,
+    /// Where launched programs live in the cgroup tree
+    pub scope: LaunchScope,
+    /// Per-app overrides of `scope`, keyed by app name
+    pub app_scopes: HashMap<String, LaunchScope>,
+    /// Keep rula running after every launch, as Alt+Enter does for one
+    pub keep_open: bool,
+    /// Workspace to switch to after launching an app, keyed by app name
+    pub workspaces: HashMap<String, String>,
+}
+
+impl Default for LaunchConfig {
+    fn default() -> Self {
+        Self {
+            root_command: "pkexec".to_string(),
+            scope: LaunchScope::default(),
+            app_scopes: HashMap::new(),
+            keep_open: false,
+            workspaces: HashMap::new(),
+        }
+    }
+}
+
+impl LaunchConfig {
+    /// Scope for a launch; launches not tied to an app use the global setting
+    pub fn scope_for(&self, app_name: Option<&str>) -> LaunchScope {
+        app_name
+            .and_then(|name| self.app_scopes.get(name))
+            .copied()
+            .unwrap_or(self.scope)
+    }
+
+    /// Target workspace for an app launch, if a rule names one
+    pub fn workspace_for(&self, app_name: Option<&str>) -> Option<&str> {
+        app_name
+            .and_then(|name| self.workspaces.get(name))
+            .map(|w| w.as_str())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LaunchScope {
+    /// Stay in rula's (and its terminal's) cgroup
+    #[default]
+    None,
+    /// `systemd-run --user --scope`, a transient scope of its own
+    Systemd,
+    /// `uwsm app`, for uwsm-managed sessions
+    Uwsm,
+}
+
+/// `[keys.insert]` / `[keys.normal]`: action name -> key or list of keys,
+/// e.g. `quit = ["Esc", "q"]`. Each entry replaces that action's default keys.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct KeysConfig {
+    pub insert: HashMap<String, KeyList>,
+    pub normal: HashMap<String, KeyList>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+pub enum KeyList {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl KeyList {
+    pub fn keys(&self) -> &[String] {
+        match self {
+            KeyList::One(key) => std::slice::from_ref(key),
+            KeyList::Many(keys) => keys,
+        }
+    }
+}
+
+/// One `[[entries]]` item
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct CustomEntry {
+    pub name: String,
+    pub exec: String,
+    /// Run inside a terminal, like `Terminal=true` in a .desktop file
+    pub terminal: bool,
+    /// `Icon=`-style theme name, used to pick a glyph
+    pub icon: String,
+}
+
+/// One `[[plugins]]` item: `command` is run with the query appended as the last
+/// argument and prints one JSON item per line
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginConfig {
+    /// Shown as the prompt, e.g. "Todo"
+    pub name: String,
+    pub command: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct AppsConfig {
+    /// Hide apps (until Ctrl+h) once they go this many days without a launch
+    pub dormant_days: u64,
+}
+
+impl Default for AppsConfig {
+    fn default() -> Self {
+        Self { dormant_days: 30 }
+    }
+}
+
+impl AppsConfig {
+    pub fn dormant_secs(&self) -> u64 {
+        self.dormant_days * 24 * 60 * 60
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ProjectsConfig {
+    /// Directories scanned for git repositories
+    pub roots: Vec<String>,
+    /// How deep below each root to look for a `.git` directory
+    pub max_depth: usize,
+}
+
+impl Default for ProjectsConfig {
+    fn default() -> Self {
+        Self {
+            roots: vec!["~/code".to_string(), "~/projects".to_string()],
+            max_depth: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FilesConfig {
+    /// Wait this long after the last keystroke before searching
+    pub debounce_ms: u64,
+    /// Directories searched (and indexed) by Files and Grep mode
+    pub roots: Vec<FileRoot>,
+    /// Gitignore-style globs skipped under every root, e.g. "node_modules"
+    pub exclude: Vec<String>,
+    /// Show the selected file's first lines beside the results (toggle with `p`)
+    pub preview: bool,
+}
+
+impl Default for FilesConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: 80,
+            roots: vec![FileRoot::default()],
+            exclude: Vec::new(),
+            preview: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FileRoot {
+    pub path: String,
+    /// How many directory levels below `path` to search
+    pub max_depth: usize,
+}
+
+impl Default for FileRoot {
+    fn default() -> Self {
+        Self {
+            path: "~".to_string(),
+            max_depth: 5,
+        }
+    }
+}
+
+impl Config {
+    /// Load the config file, or the defaults if there is none.
+    /// A config that doesn't parse is an error rather than silently reset to defaults.
+    pub fn load() -> Result<Self> {
+        match fs::read_to_string(config_path()) {
+            Ok(text) => Ok(toml::from_str(&text)?),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+fn config_path() -> PathBuf {
+    let mut path = dirs::config_dir().unwrap_or_else(|| PathBuf::from("."));
+    path.push("rula");
+    path.push("config.toml");
+    path
+}
+
+/// Expand a leading `~` to the home directory
+pub fn expand_tilde(path: &str) -> PathBuf {
+    match (path.strip_prefix("~"), dirs::home_dir()) {
+        (Some(rest), Some(home)) => home.join(rest.trim_start_matches('/')),
+        _ => PathBuf::from(path),
+    }
+}
@@ -2,14 +2,159 @@
 // Database - SQLite persistence for app preferences and usage stats
 // ============================================================================
 
-use rusqlite::{params, Connection, Result};
+use rusqlite::{params, Connection, Params, Result, Row, TransactionBehavior};
+use std::collections::HashMap;
 use std::path::PathBuf;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::mpsc::{self, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use crate::error::Error;
+
+/// Launches kept per app for frecency; older ones only live on in `usage`
+const FRECENCY_SAMPLES: i64 = 10;
+const DAY_SECS: u64 = 24 * 60 * 60;
+/// Usage counts are halved once per period, so habits can change
+const DECAY_PERIOD_SECS: u64 = 30 * DAY_SECS;
+/// Past queries kept per mode
+const QUERY_HISTORY_LIMIT: i64 = 100;
+/// Schema steps, applied in order to bring `PRAGMA user_version` up to
+/// MIGRATIONS.len(). Never edit a released step; append a new one instead.
+const MIGRATIONS: &[&str] = &[
+    // 1: base schema. IF NOT EXISTS keeps it safe for databases created
+    //    before migrations were tracked (user_version 0).
+    "CREATE TABLE IF NOT EXISTS app_prefs (
+        app_name TEXT PRIMARY KEY,
+        is_tui BOOLEAN NOT NULL DEFAULT 0,
+        score INTEGER NOT NULL DEFAULT 0,
+        usage INTEGER NOT NULL DEFAULT 0,
+        last_used INTEGER DEFAULT 0
+    );
+    -- Recent launch times, the recency half of frecency
+    CREATE TABLE IF NOT EXISTS launches (
+        app_name TEXT NOT NULL,
+        launched_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS launches_by_app ON launches (app_name, launched_at);
+    CREATE TABLE IF NOT EXISTS history (
+        program TEXT NOT NULL,
+        args TEXT NOT NULL,
+        is_tui BOOLEAN NOT NULL DEFAULT 0,
+        launched_at INTEGER NOT NULL
+    );
+    CREATE TABLE IF NOT EXISTS meta (
+        key TEXT PRIMARY KEY,
+        value INTEGER NOT NULL
+    );",
+    // 2: one row per launch attempt, written off the launch path by EventLog
+    "CREATE TABLE IF NOT EXISTS launch_events (
+        app TEXT NOT NULL,
+        mode TEXT NOT NULL,
+        success BOOLEAN NOT NULL,
+        launched_at INTEGER NOT NULL
+    );
+    CREATE INDEX IF NOT EXISTS launch_events_by_time ON launch_events (launched_at);",
+    // 3: queries that led to a launch, recalled with Up / Ctrl+R
+    "CREATE TABLE IF NOT EXISTS queries (
+        mode TEXT NOT NULL,
+        query TEXT NOT NULL,
+        used_at INTEGER NOT NULL,
+        PRIMARY KEY (mode, query)
+    );",
+];
+
+/// Apply every migration newer than the database, each in its own transaction
+fn migrate(conn: &Connection) -> Result<()> {
+    let version: i64 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+    for (i, step) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        let tx = conn.unchecked_transaction()?;
+        tx.execute_batch(step)?;
+        tx.pragma_update(None, "user_version", i as i64 + 1)?;
+        tx.commit()?;
+    }
+    Ok(())
+}
+
+/// Tables carried by --export / --import
+const EXPORT_TABLES: &[&str] = &["app_prefs", "launches", "history", "launch_events", "queries", "meta"];
+/// Bumped if the export layout ever changes incompatibly
+const EXPORT_VERSION: i64 = 1;
 
 pub struct Database {
     conn: Connection,
 }
 
+/// One past launch, exactly as it was spawned
+#[derive(Debug, Clone)]
+pub struct HistoryEntry {
+    pub program: String,
+    pub args: Vec<String>,
+    pub is_tui: bool,
+    pub launched_at: u64,
+}
+
+/// One launch attempt, as recorded in `launch_events`
+#[derive(Debug, Clone)]
+pub struct LaunchEvent {
+    /// App name, or the program for launches not tied to an app entry
+    pub app: String,
+    /// Mode the launch came from, e.g. "apps", "files" or "plugin:Todo"
+    pub mode: String,
+    pub success: bool,
+    pub launched_at: u64,
+}
+
+/// Writes launch events on a background thread with its own connection, so
+/// logging never delays a launch. `finish` flushes what's still queued.
+pub struct EventLog {
+    tx: Option<Sender<LaunchEvent>>,
+    writer: Option<JoinHandle<()>>,
+}
+
+impl EventLog {
+    pub fn spawn() -> Self {
+        let (tx, rx) = mpsc::channel::<LaunchEvent>();
+        let writer = thread::spawn(move || {
+            // rusqlite connections can't be shared across threads, so open our own
+            let db = match Database::new() {
+                Ok(db) => db,
+                Err(e) => {
+                    tracing::warn!("launch event log: opening database failed: {}", e);
+                    return;
+                }
+            };
+            for event in rx {
+                if let Err(e) = db.log_launch_event(&event) {
+                    tracing::warn!("logging launch of {} failed: {}", event.app, e);
+                }
+            }
+        });
+        Self { tx: Some(tx), writer: Some(writer) }
+    }
+
+    pub fn record(&self, event: LaunchEvent) {
+        if let Some(tx) = &self.tx {
+            let _ = tx.send(event);
+        }
+    }
+
+    /// Wait for queued events to be written; call before exiting
+    pub fn finish(&mut self) {
+        self.tx = None;
+        if let Some(writer) = self.writer.take() {
+            let _ = writer.join();
+        }
+    }
+}
+
+impl HistoryEntry {
+    /// Shell-quoted command line, for display and matching
+    pub fn command_line(&self) -> String {
+        let mut words = vec![self.program.as_str()];
+        words.extend(self.args.iter().map(|a| a.as_str()));
+        shell_words::join(words)
+    }
+}
+
 impl Database {
     pub fn new() -> Result<Self> {
         let mut path = dirs::data_local_dir().unwrap_or(PathBuf::from("."));
@@ -19,19 +164,25 @@ impl Database {
         path.push("db.sqlite");
         let conn = Connection::open(path)?;
 
-        // Create table with all needed fields
-        conn.execute(
-            "CREATE TABLE IF NOT EXISTS app_prefs (
-                app_name TEXT PRIMARY KEY,
-                is_tui BOOLEAN NOT NULL DEFAULT 0,
-                score INTEGER NOT NULL DEFAULT 0,
-                usage INTEGER NOT NULL DEFAULT 0,
-                last_used INTEGER DEFAULT 0
-            )",
-            [],
-        )?;
+        // The watcher daemon, background scans and launcher instances share the
+        // file: WAL lets readers run alongside a writer, and the timeout waits
+        // out short write locks instead of failing with "database is locked"
+        conn.pragma_update(None, "journal_mode", "WAL")?;
+        conn.busy_timeout(Duration::from_secs(5))?;
+        Self::init(conn)
+    }
+
+    /// A fresh database that lives and dies with the connection, for tests
+    #[cfg(test)]
+    pub fn in_memory() -> Result<Self> {
+        Self::init(Connection::open_in_memory()?)
+    }
 
-        Ok(Self { conn })
+    fn init(conn: Connection) -> Result<Self> {
+        migrate(&conn)?;
+        let mut db = Self { conn };
+        db.decay_usage()?;
+        Ok(db)
     }
 
     /// Get all app data: (is_tui, score, usage, last_used)
@@ -84,24 +235,388 @@ impl Database {
         map
     }
 
-    /// Increment usage count and update last_used timestamp
-    pub fn increment_usage(&self, app_name: &str) -> Result<()> {
+    /// Increment usage count and update last_used timestamp.
+    /// `is_tui` is only stored when this creates the row, so an existing preference is kept.
+    pub fn increment_usage(&self, app_name: &str, is_tui: bool) -> Result<()> {
         let now = SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap()
             .as_secs();
 
         self.conn.execute(
-            "INSERT INTO app_prefs (app_name, usage, last_used) VALUES (?1, 1, ?2)
+            "INSERT INTO app_prefs (app_name, is_tui, usage, last_used) VALUES (?1, ?3, 1, ?2)
              ON CONFLICT(app_name) DO UPDATE SET
                 usage = usage + 1,
                 last_used = ?2",
+            params![app_name, now as i64, is_tui],
+        )?;
+
+        self.conn.execute(
+            "INSERT INTO launches (app_name, launched_at) VALUES (?1, ?2)",
             params![app_name, now as i64],
         )?;
+        self.conn.execute(
+            "DELETE FROM launches WHERE app_name = ?1 AND rowid NOT IN (
+                SELECT rowid FROM launches WHERE app_name = ?1
+                ORDER BY launched_at DESC LIMIT ?2
+             )",
+            params![app_name, FRECENCY_SAMPLES],
+        )?;
+
+        Ok(())
+    }
+
+    /// Record a launch as spawned; `args` are stored as a JSON array
+    pub fn record_launch(&self, program: &str, args: &[String], is_tui: bool) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+        let args = serde_json::to_string(args).unwrap_or_else(|_| "[]".to_string());
+
+        self.conn.execute(
+            "INSERT INTO history (program, args, is_tui, launched_at) VALUES (?1, ?2, ?3, ?4)",
+            params![program, args, is_tui, now as i64],
+        )?;
 
         Ok(())
     }
 
+    pub fn log_launch_event(&self, event: &LaunchEvent) -> Result<()> {
+        self.conn.execute(
+            "INSERT INTO launch_events (app, mode, success, launched_at) VALUES (?1, ?2, ?3, ?4)",
+            params![event.app, event.mode, event.success, event.launched_at as i64],
+        )?;
+        Ok(())
+    }
+
+    /// Remember a query that led to a launch, keeping the newest per mode
+    pub fn record_query(&self, mode: &str, query: &str) -> Result<()> {
+        if query.trim().is_empty() {
+            return Ok(());
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        self.conn.execute(
+            "INSERT INTO queries (mode, query, used_at) VALUES (?1, ?2, ?3)
+             ON CONFLICT(mode, query) DO UPDATE SET used_at = excluded.used_at",
+            params![mode, query, now as i64],
+        )?;
+        self.conn.execute(
+            "DELETE FROM queries WHERE mode = ?1 AND query NOT IN
+             (SELECT query FROM queries WHERE mode = ?1 ORDER BY used_at DESC, rowid DESC LIMIT ?2)",
+            params![mode, QUERY_HISTORY_LIMIT],
+        )?;
+        Ok(())
+    }
+
+    /// Past queries of a mode, most recent first
+    pub fn recent_queries(&self, mode: &str) -> Vec<String> {
+        self.read_rows(
+            "recent queries",
+            "SELECT query FROM queries WHERE mode = ?1 ORDER BY used_at DESC, rowid DESC",
+            params![mode],
+            |row| row.get(0),
+        )
+    }
+
+    /// Distinct past launches, most recent first
+    pub fn launch_history(&self, limit: usize) -> Vec<HistoryEntry> {
+        self.read_rows(
+            "launch history",
+            "SELECT program, args, is_tui, MAX(launched_at) AS last FROM history
+             GROUP BY program, args, is_tui
+             ORDER BY last DESC
+             LIMIT ?1",
+            params![limit as i64],
+            |row| {
+                let args: String = row.get(1)?;
+                Ok(HistoryEntry {
+                    program: row.get(0)?,
+                    args: serde_json::from_str(&args).unwrap_or_default(),
+                    is_tui: row.get(2)?,
+                    launched_at: row.get::<_, i64>(3)? as u64,
+                })
+            },
+        )
+    }
+
+    /// Most launched apps by their (decayed) usage count
+    pub fn top_apps(&self, limit: usize) -> Vec<(String, i32)> {
+        self.read_rows(
+            "top apps",
+            "SELECT app_name, usage FROM app_prefs WHERE usage > 0 ORDER BY usage DESC, app_name LIMIT ?1",
+            params![limit as i64],
+            |row| Ok((row.get(0)?, row.get(1)?)),
+        )
+    }
+
+    /// Every launch attempt since `since`, oldest first: (launched_at, success)
+    pub fn launch_times_since(&self, since: u64) -> Vec<(u64, bool)> {
+        self.read_rows(
+            "launch events",
+            "SELECT launched_at, success FROM launch_events WHERE launched_at >= ?1 ORDER BY launched_at",
+            params![since as i64],
+            |row| Ok((row.get::<_, i64>(0)? as u64, row.get(1)?)),
+        )
+    }
+
+    /// Apps used before but not since `before`, longest idle first: (name, last_used)
+    pub fn idle_apps(&self, before: u64, limit: usize) -> Vec<(String, u64)> {
+        self.read_rows(
+            "idle apps",
+            "SELECT app_name, last_used FROM app_prefs
+             WHERE last_used > 0 AND last_used < ?1
+             ORDER BY last_used LIMIT ?2",
+            params![before as i64, limit as i64],
+            |row| Ok((row.get(0)?, row.get::<_, i64>(1)? as u64)),
+        )
+    }
+
+    /// Frecency per app, Firefox-style: the launch count scaled by the average
+    /// recency weight of the latest launches, so old heavy use fades out
+    pub fn get_all_frecency(&self) -> HashMap<String, i32> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut samples: HashMap<String, Vec<u64>> = HashMap::new();
+        if let Ok(mut stmt) = self.conn.prepare("SELECT app_name, launched_at FROM launches") {
+            let rows = stmt.query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, i64>(1)? as u64))
+            });
+            if let Ok(rows) = rows {
+                for (name, at) in rows.flatten() {
+                    samples.entry(name).or_default().push(at);
+                }
+            }
+        }
+
+        let mut scores = HashMap::new();
+        for (name, (_, _, usage, last_used)) in self.get_all_app_data() {
+            if usage <= 0 {
+                continue;
+            }
+            // Rows from before launch history existed only know their last launch
+            let times = match samples.get(&name) {
+                Some(times) => times.as_slice(),
+                None => std::slice::from_ref(&last_used),
+            };
+            scores.insert(name, frecency_score(now, usage, times));
+        }
+
+        scores
+    }
+
+    /// Frecency of a single app, the same score `get_all_frecency` gives it
+    pub fn frecency(&self, app_name: &str) -> i32 {
+        let (_, _, usage, last_used) = self.get_app_data(app_name);
+        if usage <= 0 {
+            return 0;
+        }
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        let mut times = self.read_rows(
+            "launch times",
+            "SELECT launched_at FROM launches WHERE app_name = ?1",
+            params![app_name],
+            |row| Ok(row.get::<_, i64>(0)? as u64),
+        );
+        if times.is_empty() {
+            times.push(last_used);
+        }
+        frecency_score(now, usage, &times)
+    }
+
+    /// Rows of a read-only query; a failure is logged and reads as no rows,
+    /// so a damaged table empties one view instead of failing the launcher
+    fn read_rows<T>(
+        &self,
+        what: &str,
+        sql: &str,
+        params: impl Params,
+        map: impl FnMut(&Row<'_>) -> Result<T>,
+    ) -> Vec<T> {
+        let rows = self
+            .conn
+            .prepare(sql)
+            .and_then(|mut stmt| Ok(stmt.query_map(params, map)?.flatten().collect()));
+        rows.unwrap_or_else(|e| {
+            tracing::warn!("reading {} failed: {}", what, e);
+            Vec::new()
+        })
+    }
+
+    /// Halve every usage count once for each full decay period since the last
+    /// decay. Applied lazily whenever the database is opened.
+    fn decay_usage(&mut self) -> Result<()> {
+        let now = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs();
+
+        // Immediate takes the write lock before reading last_decay, so a second
+        // process opening the database waits and then sees the updated clock
+        // instead of halving the same period again
+        let tx = self.conn.transaction_with_behavior(TransactionBehavior::Immediate)?;
+        let last_decay: Option<i64> = tx
+            .query_row("SELECT value FROM meta WHERE key = 'last_decay'", [], |row| row.get(0))
+            .ok();
+
+        // First run: start the clock instead of decaying existing counts
+        let last_decay = match last_decay {
+            Some(at) => at as u64,
+            None => {
+                set_last_decay(&tx, now)?;
+                return tx.commit();
+            }
+        };
+
+        let periods = now.saturating_sub(last_decay) / DECAY_PERIOD_SECS;
+        if periods == 0 {
+            return Ok(());
+        }
+
+        // Past 31 halvings every i32 count is zero anyway
+        let periods = periods.min(31);
+        tx.execute(
+            "UPDATE app_prefs SET usage = usage >> ?1 WHERE usage > 0",
+            params![periods as i64],
+        )?;
+        // Keep the remainder so decay stays on a fixed schedule
+        set_last_decay(&tx, last_decay + periods * DECAY_PERIOD_SECS)?;
+        tx.commit()
+    }
+
+    /// Delete apps missing from `installed` whose last launch is older than
+    /// `idle_before` (or that were never launched), with their launch times.
+    /// Returns the removed names.
+    pub fn prune_apps(&self, installed: &std::collections::HashSet<String>, idle_before: u64) -> Result<Vec<String>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let stale: Vec<String> = tx
+            .prepare("SELECT app_name FROM app_prefs WHERE COALESCE(last_used, 0) < ?1 ORDER BY app_name")?
+            .query_map(params![idle_before as i64], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?
+            .into_iter()
+            .filter(|name| !installed.contains(name))
+            .collect();
+
+        for name in &stale {
+            tx.execute("DELETE FROM app_prefs WHERE app_name = ?1", params![name])?;
+            tx.execute("DELETE FROM launches WHERE app_name = ?1", params![name])?;
+        }
+        tx.commit()?;
+        Ok(stale)
+    }
+
+    /// Clear usage and launch history of apps last used before `idle_before`,
+    /// keeping their terminal flag and base score. Returns the reset names.
+    pub fn reset_idle_apps(&self, idle_before: u64) -> Result<Vec<String>> {
+        let tx = self.conn.unchecked_transaction()?;
+        let idle: Vec<String> = tx
+            .prepare("SELECT app_name FROM app_prefs WHERE last_used > 0 AND last_used < ?1 ORDER BY app_name")?
+            .query_map(params![idle_before as i64], |row| row.get::<_, String>(0))?
+            .collect::<Result<Vec<_>>>()?;
+
+        for name in &idle {
+            tx.execute("UPDATE app_prefs SET usage = 0, last_used = 0 WHERE app_name = ?1", params![name])?;
+            tx.execute("DELETE FROM launches WHERE app_name = ?1", params![name])?;
+        }
+        tx.commit()?;
+        Ok(idle)
+    }
+
+    // ========================================================================
+    // Export / Import
+    // ========================================================================
+
+    /// Every row of the exported tables as JSON objects keyed by column name:
+    /// `{"version": 1, "tables": {"app_prefs": [{...}], ...}}`
+    pub fn export_json(&self) -> Result<serde_json::Value> {
+        let mut tables = serde_json::Map::new();
+        for table in EXPORT_TABLES {
+            let mut stmt = self.conn.prepare(&format!("SELECT * FROM {}", table))?;
+            let columns: Vec<String> = stmt.column_names().iter().map(|c| c.to_string()).collect();
+            let rows = stmt.query_map([], |row| {
+                let mut object = serde_json::Map::new();
+                for (i, column) in columns.iter().enumerate() {
+                    object.insert(column.clone(), sql_to_json(row.get_ref(i)?));
+                }
+                Ok(serde_json::Value::Object(object))
+            })?;
+            tables.insert(table.to_string(), rows.collect::<Result<Vec<_>>>()?.into());
+        }
+
+        Ok(serde_json::json!({ "version": EXPORT_VERSION, "tables": tables }))
+    }
+
+    /// Replace the exported tables with the rows of an export, all or nothing.
+    /// Tables missing from the file are left alone; unknown tables and
+    /// columns are skipped. Returns the number of rows restored.
+    pub fn import_json(&self, data: &serde_json::Value) -> crate::error::Result<usize> {
+        let invalid = |message: &str| Error::ImportFormat(message.to_string());
+        if data.get("version").and_then(|v| v.as_i64()) != Some(EXPORT_VERSION) {
+            return Err(invalid("not a rula export, or from an incompatible version"));
+        }
+        let tables = data.get("tables").and_then(|t| t.as_object()).ok_or_else(|| invalid("missing \"tables\""))?;
+        let well_formed = EXPORT_TABLES
+            .iter()
+            .filter_map(|table| tables.get(*table).and_then(|rows| rows.as_array()))
+            .flatten()
+            .all(|row| row.is_object());
+        if !well_formed {
+            return Err(invalid("rows must be objects"));
+        }
+
+        self.restore_tables(tables).map_err(Error::Import)
+    }
+
+    /// Write checked export tables in one transaction
+    fn restore_tables(&self, tables: &serde_json::Map<String, serde_json::Value>) -> Result<usize> {
+        let tx = self.conn.unchecked_transaction()?;
+        let mut restored = 0;
+        for table in EXPORT_TABLES {
+            let Some(rows) = tables.get(*table).and_then(|rows| rows.as_array()) else {
+                continue;
+            };
+
+            // Only real column names ever reach the SQL text
+            let known: Vec<String> = tx
+                .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?
+                .query_map([], |row| row.get(0))?
+                .collect::<Result<_>>()?;
+
+            tx.execute(&format!("DELETE FROM {}", table), [])?;
+            for object in rows.iter().filter_map(|row| row.as_object()) {
+                let (columns, values): (Vec<&String>, Vec<rusqlite::types::Value>) = object
+                    .iter()
+                    .filter(|(column, _)| known.contains(column))
+                    .map(|(column, value)| (column, json_to_sql(value)))
+                    .unzip();
+                if columns.is_empty() {
+                    continue;
+                }
+
+                let names: Vec<&str> = columns.iter().map(|c| c.as_str()).collect();
+                let placeholders: Vec<String> = (1..=values.len()).map(|i| format!("?{}", i)).collect();
+                tx.execute(
+                    &format!("INSERT INTO {} ({}) VALUES ({})", table, names.join(", "), placeholders.join(", ")),
+                    rusqlite::params_from_iter(values),
+                )?;
+                restored += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(restored)
+    }
+
     /// Set TUI mode preference for an app
     pub fn set_tui_mode(&self, app_name: &str, is_tui: bool) -> Result<()> {
         self.conn.execute(
@@ -124,6 +639,18 @@ impl Database {
         Ok(())
     }
 
+    /// Add `delta` to an app's base score, creating its row if needed.
+    /// Returns the new base score.
+    pub fn adjust_base_score(&self, app_name: &str, delta: i32) -> Result<i32> {
+        self.conn.query_row(
+            "INSERT INTO app_prefs (app_name, score) VALUES (?1, ?2)
+             ON CONFLICT(app_name) DO UPDATE SET score = score + ?2
+             RETURNING score",
+            params![app_name, delta],
+            |row| row.get(0),
+        )
+    }
+
     /// Check if an app has a database entry
     pub fn has_entry(&self, app_name: &str) -> bool {
         let stmt = self
@@ -144,3 +671,58 @@ impl Database {
         is_tui
     }
 }
+
+fn sql_to_json(value: rusqlite::types::ValueRef) -> serde_json::Value {
+    use rusqlite::types::ValueRef;
+    match value {
+        ValueRef::Null => serde_json::Value::Null,
+        ValueRef::Integer(i) => i.into(),
+        ValueRef::Real(f) => f.into(),
+        ValueRef::Text(t) => String::from_utf8_lossy(t).into(),
+        // No exported table stores blobs; keep the bytes as a number array
+        ValueRef::Blob(b) => b.to_vec().into(),
+    }
+}
+
+fn json_to_sql(value: &serde_json::Value) -> rusqlite::types::Value {
+    use rusqlite::types::Value;
+    match value {
+        serde_json::Value::Null => Value::Null,
+        serde_json::Value::Bool(b) => Value::Integer(*b as i64),
+        serde_json::Value::Number(n) => match n.as_i64() {
+            Some(i) => Value::Integer(i),
+            None => Value::Real(n.as_f64().unwrap_or(0.0)),
+        },
+        serde_json::Value::String(s) => Value::Text(s.clone()),
+        other => Value::Text(other.to_string()),
+    }
+}
+
+fn set_last_decay(conn: &Connection, at: u64) -> Result<()> {
+    conn.execute(
+        "INSERT INTO meta (key, value) VALUES ('last_decay', ?1)
+         ON CONFLICT(key) DO UPDATE SET value = ?1",
+        params![at as i64],
+    )?;
+    Ok(())
+}
+
+/// `usage` scaled by the average recency weight of the launch `times`
+fn frecency_score(now: u64, usage: i32, times: &[u64]) -> i32 {
+    let weights: i32 = times
+        .iter()
+        .map(|&at| recency_weight(now.saturating_sub(at)))
+        .sum();
+    usage * weights / times.len() as i32
+}
+
+/// Recency buckets: a launch this week counts ten times one from last year
+fn recency_weight(age_secs: u64) -> i32 {
+    match age_secs / DAY_SECS {
+        0..=3 => 100,
+        4..=13 => 70,
+        14..=30 => 50,
+        31..=89 => 30,
+        _ => 10,
+    }
+}
@@ -0,0 +1,49 @@
+// ============================================================================
+// Errors - One error type for everything that can fail
+// ============================================================================
+
+use std::fmt;
+use std::io;
+use thiserror::Error;
+
+#[derive(Error)]
+pub enum Error {
+    #[error("{0}")]
+    Io(#[from] io::Error),
+
+    #[error("database: {0}")]
+    Db(#[from] rusqlite::Error),
+
+    #[error("invalid JSON: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("invalid config.toml: {0}")]
+    Config(#[from] toml::de::Error),
+
+    #[error("file watcher: {0}")]
+    Watch(#[from] notify::Error),
+
+    /// An --import file that isn't a usable rula export
+    #[error("invalid export file: {0}")]
+    ImportFormat(String),
+
+    #[error("import failed: {0}")]
+    Import(rusqlite::Error),
+
+    #[error("failed to launch {program}: {source}")]
+    Launch { program: String, source: io::Error },
+
+    /// Bad command line arguments, with what was expected
+    #[error("{0}")]
+    Usage(String),
+}
+
+// `main` returns this error and Rust prints its Debug form on exit,
+// so show the message rather than the variant structure
+impl fmt::Debug for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
@@ -0,0 +1,111 @@
+// ============================================================================
+// Icons - Nerd font glyphs resolved from Icon= and Categories=
+// ============================================================================
+
+use std::collections::HashMap;
+use std::path::Path;
+use crate::system::AppEntry;
+
+const TUI_ICON: &str = "\u{e795}";
+const GENERIC_APP_ICON: &str = "\u{f2d0}";
+
+/// Known `Icon=` names, matched as substrings of the lowercased icon name
+/// (so "org.mozilla.firefox" and "firefox-developer-edition" both hit "firefox")
+const ICON_NAME_GLYPHS: &[(&str, &str)] = &[
+    ("firefox", "\u{f0239}"),
+    ("librewolf", "\u{f0239}"),
+    ("chromium", "\u{f268}"),
+    ("chrome", "\u{f268}"),
+    ("brave", "\u{f268}"),
+    ("kitty", "\u{f011b}"),
+    ("alacritty", "\u{f489}"),
+    ("foot", "\u{f489}"),
+    ("wezterm", "\u{f489}"),
+    ("nvim", "\u{f36f}"),
+    ("vim", "\u{e62b}"),
+    ("vscode", "\u{f0a1e}"),
+    ("visual-studio-code", "\u{f0a1e}"),
+    ("git", "\u{e702}"),
+    ("python", "\u{e73c}"),
+    ("steam", "\u{f1b6}"),
+    ("discord", "\u{f066f}"),
+    ("vesktop", "\u{f066f}"),
+    ("spotify", "\u{f1bc}"),
+    ("gimp", "\u{f338}"),
+    ("nautilus", "\u{f07b}"),
+    ("thunar", "\u{f07b}"),
+    ("dolphin", "\u{f07b}"),
+    ("thunderbird", "\u{f0e0}"),
+    ("obsproject", "\u{f03d}"),
+    ("mpv", "\u{f008}"),
+    ("vlc", "\u{f008}"),
+];
+
+/// Desktop categories, checked in order so specific ones win over broad ones
+const CATEGORY_GLYPHS: &[(&str, &str)] = &[
+    ("WebBrowser", "\u{f0ac}"),
+    ("TerminalEmulator", "\u{f489}"),
+    ("TextEditor", "\u{f15c}"),
+    ("IDE", "\u{f121}"),
+    ("FileManager", "\u{f07b}"),
+    ("Email", "\u{f0e0}"),
+    ("InstantMessaging", "\u{f086}"),
+    ("Chat", "\u{f086}"),
+    ("Monitor", "\u{f0e4}"),
+    ("Game", "\u{f0297}"),
+    ("Audio", "\u{f001}"),
+    ("Video", "\u{f008}"),
+    ("AudioVideo", "\u{f008}"),
+    ("Graphics", "\u{f03e}"),
+    ("Office", "\u{f0219}"),
+    ("Development", "\u{f121}"),
+    ("Settings", "\u{f013}"),
+    ("Education", "\u{f0c3}"),
+    ("Science", "\u{f0c3}"),
+    ("Network", "\u{f0ac}"),
+    ("System", "\u{f108}"),
+    ("Utility", "\u{f0ad}"),
+];
+
+/// Resolve the glyph for an app: user overrides, then Icon=, then Categories=
+pub fn app_icon<'a>(app: &AppEntry, is_tui: bool, overrides: &'a HashMap<String, String>) -> &'a str {
+    let icon_name = icon_name(&app.icon);
+
+    // User overrides by app name, icon name, or category
+    let user = overrides
+        .get(&app.name)
+        .or_else(|| overrides.get(&icon_name))
+        .or_else(|| app.categories.iter().find_map(|c| overrides.get(c)));
+    if let Some(glyph) = user {
+        return glyph;
+    }
+
+    if !icon_name.is_empty() {
+        if let Some((_, glyph)) = ICON_NAME_GLYPHS.iter().find(|(key, _)| icon_name.contains(key)) {
+            return glyph;
+        }
+    }
+
+    if let Some((_, glyph)) = CATEGORY_GLYPHS
+        .iter()
+        .find(|(category, _)| app.categories.iter().any(|c| c == category))
+    {
+        return glyph;
+    }
+
+    if is_tui {
+        TUI_ICON
+    } else if !app.desktop_path.is_empty() {
+        GENERIC_APP_ICON
+    } else {
+        ""
+    }
+}
+
+/// `Icon=` may be a bare theme name or an absolute path to an image
+fn icon_name(icon: &str) -> String {
+    Path::new(icon)
+        .file_stem()
+        .map(|s| s.to_string_lossy().to_lowercase())
+        .unwrap_or_default()
+}
@@ -0,0 +1,276 @@
+// ============================================================================
+// File Index - Persistent, incrementally refreshed file list for Files mode
+// ============================================================================
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::config::{expand_tilde, FilesConfig};
+use crate::system::get_cache_path;
+
+const INDEX_FILE: &str = "files.json";
+/// Older indexes are still used immediately, but refreshed in the background
+const STALE_AFTER_SECS: u64 = 5 * 60;
+
+/// Where Files and Grep mode look: the configured roots and exclusion globs,
+/// with `~` already expanded
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct SearchScope {
+    pub roots: Vec<ScopeRoot>,
+    pub exclude: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScopeRoot {
+    pub path: PathBuf,
+    pub max_depth: usize,
+}
+
+impl SearchScope {
+    pub fn from_config(files: &FilesConfig) -> Self {
+        Self {
+            roots: files
+                .roots
+                .iter()
+                .map(|r| ScopeRoot {
+                    path: expand_tilde(&r.path),
+                    max_depth: r.max_depth,
+                })
+                .collect(),
+            exclude: files.exclude.clone(),
+        }
+    }
+
+    /// Walker over `dir` (which lies inside `root`), honoring .gitignore/.ignore
+    /// and the exclusion globs. Globs are matched relative to the root.
+    pub fn walker(&self, root: &ScopeRoot, dir: &Path, max_depth: usize) -> ignore::Walk {
+        let mut overrides = ignore::overrides::OverrideBuilder::new(&root.path);
+        for glob in &self.exclude {
+            // A leading '!' turns an override into an ignore rule
+            let _ = overrides.add(&format!("!{}", glob));
+        }
+
+        let mut builder = ignore::WalkBuilder::new(dir);
+        builder
+            .hidden(false)
+            .max_depth(Some(max_depth))
+            .git_ignore(true)
+            .ignore(true);
+        if let Ok(overrides) = overrides.build() {
+            builder.overrides(overrides);
+        }
+        builder.build()
+    }
+
+    /// Live walk of every root, yielding files as full paths
+    pub fn walk_files(&self) -> impl Iterator<Item = String> + '_ {
+        self.roots.iter().flat_map(move |root| {
+            self.walker(root, &root.path, root.max_depth)
+                .flatten()
+                .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
+                .map(|e| e.path().to_string_lossy().to_string())
+        })
+    }
+
+    pub fn contains(&self, path: &Path) -> bool {
+        self.roots.iter().any(|r| path.starts_with(&r.path))
+    }
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct FileIndex {
+    /// The scope this index was built for; a config change forces a full rebuild
+    scope: SearchScope,
+    built_at: u64,
+    dirs: HashMap<String, IndexedDir>,
+}
+
+/// One directory level. Its mtime only changes when direct children are added,
+/// removed or renamed, so an unchanged mtime means the listing can be reused.
+#[derive(Serialize, Deserialize, Clone, Default)]
+struct IndexedDir {
+    mtime: u64,
+    files: Vec<String>,
+    subdirs: Vec<String>,
+}
+
+impl FileIndex {
+    pub fn load() -> io::Result<Self> {
+        let json = fs::read_to_string(get_cache_path(INDEX_FILE))?;
+        Ok(serde_json::from_str(&json)?)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        let json = serde_json::to_string(self)?;
+        fs::write(get_cache_path(INDEX_FILE), json)?;
+        Ok(())
+    }
+
+    /// Full walk of every root, honoring ignore files and exclusions like the live search
+    pub fn build(scope: &SearchScope) -> Self {
+        let mut dirs: HashMap<String, IndexedDir> = HashMap::new();
+
+        let walker = scope
+            .roots
+            .iter()
+            .flat_map(|root| scope.walker(root, &root.path, root.max_depth));
+
+        for entry in walker.flatten() {
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            let is_file = entry.file_type().is_some_and(|ft| ft.is_file());
+
+            if is_dir {
+                let path = entry.path().to_string_lossy().to_string();
+                dirs.entry(path).or_default().mtime = dir_mtime(entry.path());
+            }
+
+            if entry.depth() == 0 || !(is_dir || is_file) {
+                continue;
+            }
+
+            let (parent, name) = match (entry.path().parent(), entry.path().file_name()) {
+                (Some(p), Some(n)) => (p.to_string_lossy().to_string(), n.to_string_lossy().to_string()),
+                _ => continue,
+            };
+            let listing = dirs.entry(parent).or_default();
+            if is_dir {
+                listing.subdirs.push(name);
+            } else {
+                listing.files.push(name);
+            }
+        }
+
+        Self {
+            scope: scope.clone(),
+            built_at: now_secs(),
+            dirs,
+        }
+    }
+
+    /// Re-list only directories whose mtime changed since the last run.
+    /// Falls back to a full build when the index was built for another scope.
+    pub fn refresh(&self, scope: &SearchScope) -> Self {
+        if self.scope != *scope || self.dirs.is_empty() {
+            return Self::build(scope);
+        }
+
+        let mut dirs = HashMap::with_capacity(self.dirs.len());
+        for root in &scope.roots {
+            self.refresh_dir(scope, root, &root.path, 0, &mut dirs);
+        }
+
+        Self {
+            scope: scope.clone(),
+            built_at: now_secs(),
+            dirs,
+        }
+    }
+
+    fn refresh_dir(
+        &self,
+        scope: &SearchScope,
+        root: &ScopeRoot,
+        dir: &Path,
+        depth: usize,
+        out: &mut HashMap<String, IndexedDir>,
+    ) {
+        // Directory vanished since the last run - drop it and everything below
+        let mtime = match fs::metadata(dir) {
+            Ok(m) if m.is_dir() => mtime_of(&m),
+            _ => return,
+        };
+
+        let key = dir.to_string_lossy().to_string();
+        let listing = match self.dirs.get(&key) {
+            Some(cached) if cached.mtime == mtime => cached.clone(),
+            _ => list_dir(scope, root, dir, depth, mtime),
+        };
+
+        if depth < root.max_depth {
+            for sub in &listing.subdirs {
+                self.refresh_dir(scope, root, &dir.join(sub), depth + 1, out);
+            }
+        }
+        out.insert(key, listing);
+    }
+
+    pub fn is_stale(&self) -> bool {
+        now_secs().saturating_sub(self.built_at) > STALE_AFTER_SECS
+    }
+
+    /// Every indexed directory, e.g. for filesystem watches
+    pub fn dir_paths(&self) -> Vec<PathBuf> {
+        self.dirs.keys().map(PathBuf::from).collect()
+    }
+
+    /// Every indexed file as a full path
+    pub fn paths(&self) -> Vec<String> {
+        let mut paths = Vec::new();
+        for (dir, listing) in &self.dirs {
+            let dir = PathBuf::from(dir);
+            paths.extend(
+                listing
+                    .files
+                    .iter()
+                    .map(|f| dir.join(f).to_string_lossy().to_string()),
+            );
+        }
+        paths
+    }
+}
+
+/// Load the saved index (or start fresh), bring it up to date, and persist it
+pub fn update_file_index(scope: &SearchScope) -> io::Result<FileIndex> {
+    let index = FileIndex::load().unwrap_or_default().refresh(scope);
+    index.save()?;
+    Ok(index)
+}
+
+/// List one directory level; the walker still applies ignore files from parent dirs
+fn list_dir(scope: &SearchScope, root: &ScopeRoot, dir: &Path, depth: usize, mtime: u64) -> IndexedDir {
+    let mut listing = IndexedDir {
+        mtime,
+        ..Default::default()
+    };
+
+    // Directories at the depth limit are recorded but never descended into
+    if depth >= root.max_depth {
+        return listing;
+    }
+
+    for entry in scope.walker(root, dir, 1).flatten().filter(|e| e.depth() == 1) {
+        let name = entry.file_name().to_string_lossy().to_string();
+        match entry.file_type() {
+            Some(ft) if ft.is_dir() => listing.subdirs.push(name),
+            Some(ft) if ft.is_file() => listing.files.push(name),
+            _ => {}
+        }
+    }
+
+    listing
+}
+
+fn dir_mtime(path: &Path) -> u64 {
+    fs::metadata(path).map(|m| mtime_of(&m)).unwrap_or(0)
+}
+
+fn mtime_of(metadata: &fs::Metadata) -> u64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos() as u64)
+        .unwrap_or(0)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
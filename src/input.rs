@@ -4,20 +4,35 @@
 
 use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyModifiers};
 use std::time::Duration;
-use crate::app::{App, InputMode};
+use crate::app::{App, FileAction, InputMode, Mode, Motion, Operator, PendingOperator, SCORE_STEP};
+use crate::config::KeysConfig;
+use crate::keymap::{Action, Keymap};
 
-pub struct InputHandler;
+/// Terminal events the main loop reacts to
+pub enum Input {
+    Key(KeyEvent),
+    /// New width and height
+    Resize(u16, u16),
+}
+
+pub struct InputHandler {
+    keymap: Keymap,
+}
 
 impl InputHandler {
-    pub fn new() -> Self {
-        Self
+    pub fn new(keys: &KeysConfig) -> Self {
+        Self {
+            keymap: Keymap::from_config(keys),
+        }
     }
 
     /// Poll for input with optional timeout
-    pub fn poll(&self, timeout_ms: u64) -> Option<KeyEvent> {
+    pub fn poll(&self, timeout_ms: u64) -> Option<Input> {
         if event::poll(Duration::from_millis(timeout_ms)).unwrap_or(false) {
-            if let Ok(Event::Key(key)) = event::read() {
-                return Some(key);
+            match event::read() {
+                Ok(Event::Key(key)) => return Some(Input::Key(key)),
+                Ok(Event::Resize(width, height)) => return Some(Input::Resize(width, height)),
+                _ => {}
             }
         }
         None
@@ -25,114 +40,157 @@ impl InputHandler {
 
     /// Process a key event and update app state
     pub fn process(&self, app: &mut App, key: KeyEvent) {
-        match app.input_mode {
-            InputMode::Insert => self.process_insert_mode(app, key),
-            InputMode::Normal => self.process_normal_mode(app, key),
+        app.launch_error = None;
+        // Any key dismisses the details panel
+        if app.app_details.is_some() {
+            app.close_app_details();
+            return;
+        }
+        if app.file_menu.is_some() {
+            self.process_file_menu(app, key);
+            return;
+        }
+        // Esc backs out of the "Open with" picker instead of quitting
+        if app.open_with.is_some() && key.code == KeyCode::Esc {
+            app.cancel_open_with();
+            return;
+        }
+
+        if let Some(pending) = app.pending_operator.take() {
+            self.process_operator(app, pending, key);
+            return;
+        }
+
+        for action in self.keymap.actions(app.input_mode, &key) {
+            if self.perform(app, action) {
+                return;
+            }
+        }
+
+        // Unbound printable keys type into the query
+        if app.input_mode == InputMode::Insert {
+            if let KeyCode::Char(c) = key.code {
+                if !key.modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) {
+                    app.insert_char(c);
+                }
+            }
         }
     }
 
-    fn process_insert_mode(&self, app: &mut App, key: KeyEvent) {
-        match key.code {
+    /// Run a bound action; false if it doesn't apply here, so the next action
+    /// bound to the same key gets its turn
+    fn perform(&self, app: &mut App, action: Action) -> bool {
+        match action {
             // Mode switching
-            KeyCode::Esc => {
-                app.enter_normal_mode();
-            }
+            Action::NormalMode => app.enter_normal_mode(),
+            Action::InsertMode => app.enter_insert_mode(),
+            Action::Append => app.append(),
+            Action::Quit => app.quit(),
+
+            // Query history
+            Action::QueryOlder if app.recalling_query() => return app.recall_query(true),
+            Action::QueryNewer if app.recalling_query() => return app.recall_query(false),
+            Action::QueryOlder | Action::QueryNewer => return false,
 
             // Navigation
-            KeyCode::Down | KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.next();
-            }
-            KeyCode::Up | KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.previous();
-            }
-            KeyCode::Left | KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.move_cursor_left();
-            }
-            KeyCode::Right | KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.move_cursor_right();
-            }
-            KeyCode::Home | KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.move_cursor_start();
-            }
-            KeyCode::End | KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.move_cursor_end();
-            }
+            Action::Next => app.next(),
+            Action::Previous => app.previous(),
+            Action::Top => app.go_top(),
+            Action::Bottom => app.go_bottom(),
+            Action::BrowseUp => app.browse_up(),
+
+            // Query editing
+            Action::CursorLeft => app.move_cursor_left(),
+            Action::CursorRight => app.move_cursor_right(),
+            Action::CursorStart => app.move_cursor_start(),
+            Action::CursorEnd => app.move_cursor_end(),
+            Action::WordForward => app.move_cursor(Motion::WordForward),
+            Action::WordBackward => app.move_cursor(Motion::WordBackward),
+            Action::WordEnd => app.move_cursor(Motion::WordEnd),
+            Action::ClearInput => app.clear_input(),
+            Action::Backspace => app.backspace(),
+            Action::Delete => app.delete_char(),
+            Action::DeleteToEnd => app.operate(Operator::Delete, Motion::LineEnd),
+            Action::ChangeToEnd => app.operate(Operator::Change, Motion::LineEnd),
+            Action::DeleteOperator => app.start_operator(Operator::Delete),
+            Action::ChangeOperator => app.start_operator(Operator::Change),
 
             // Actions
-            KeyCode::Enter => {
-                app.launch_selection();
-            }
-            KeyCode::Tab => {
-                app.toggle_mode();
-            }
-            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            Action::LaunchAsRoot => app.launch_selection_as_root(),
+            Action::LaunchAndStay => app.launch_selection_and_stay(),
+            Action::Launch => app.launch_selection(),
+            Action::ToggleMode => app.toggle_mode(),
+            Action::ToggleTui => {
                 app.toggle_tui_preference();
             }
-            KeyCode::Char('h') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.toggle_dormant();
-            }
-            KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.clear_input();
-            }
-
-            // Text input
-            KeyCode::Char(c) => {
-                app.insert_char(c);
-            }
-            KeyCode::Backspace => {
-                app.backspace();
-            }
-            KeyCode::Delete => {
-                app.delete_char();
-            }
+            Action::ToggleDormant => app.toggle_dormant(),
+            Action::RaiseScore if app.mode == Mode::Apps => app.adjust_score(SCORE_STEP),
+            Action::LowerScore if app.mode == Mode::Apps => app.adjust_score(-SCORE_STEP),
+            Action::RaiseScore | Action::LowerScore => return false,
+            Action::Editor => app.launch_selection_in_editor(),
+            Action::FileMenu => app.open_file_menu(),
+            Action::OpenWith => app.open_selection_with(),
+            Action::Yank => app.yank_selection(),
+            Action::Mark => app.toggle_mark(),
+            Action::Preview => app.toggle_preview(),
+            Action::Details => app.show_app_details(),
+        }
+        true
+    }
 
-            _ => {}
+    /// The key after `d` or `c` picks what it acts on: a motion (`dw`, `c$`),
+    /// `iw` for the word under the cursor, or the operator again for the whole
+    /// query. Any other key cancels it.
+    fn process_operator(&self, app: &mut App, pending: PendingOperator, key: KeyEvent) {
+        for action in self.keymap.actions(InputMode::Normal, &key) {
+            match action {
+                Action::InsertMode if !pending.inner => {
+                    app.pending_operator = Some(PendingOperator { inner: true, ..pending });
+                }
+                Action::WordForward if pending.inner => app.operate_inner_word(pending.operator),
+                Action::DeleteOperator if !pending.inner && pending.operator == Operator::Delete => {
+                    app.operate_line(pending.operator)
+                }
+                Action::ChangeOperator if !pending.inner && pending.operator == Operator::Change => {
+                    app.operate_line(pending.operator)
+                }
+                _ => match action.motion() {
+                    Some(motion) if !pending.inner => app.operate(pending.operator, motion),
+                    _ => continue,
+                },
+            }
+            return;
         }
     }
 
-    fn process_normal_mode(&self, app: &mut App, key: KeyEvent) {
+    fn process_file_menu(&self, app: &mut App, key: KeyEvent) {
         match key.code {
-            // Mode switching
-            KeyCode::Char('i') | KeyCode::Char('a') => {
-                app.enter_insert_mode();
+            KeyCode::Esc | KeyCode::Char('q') => {
+                app.close_file_menu();
             }
-
-            // Quit
-            KeyCode::Char('q') => {
-                app.quit();
+            KeyCode::Char('o') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.close_file_menu();
             }
-            KeyCode::Esc => {
-                app.quit();
+            KeyCode::Down | KeyCode::Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.file_menu_next();
+            }
+            KeyCode::Up | KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                app.file_menu_previous();
             }
-
-            // Navigation
             KeyCode::Char('j') | KeyCode::Down => {
-                app.next();
+                app.file_menu_next();
             }
             KeyCode::Char('k') | KeyCode::Up => {
-                app.previous();
+                app.file_menu_previous();
             }
-            KeyCode::Char('g') => {
-                app.go_top();
-            }
-            KeyCode::Char('G') => {
-                app.go_bottom();
-            }
-
-            // Actions
             KeyCode::Enter => {
-                app.launch_selection();
-            }
-            KeyCode::Tab => {
-                app.toggle_mode();
-            }
-            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                app.toggle_tui_preference();
+                app.run_file_action(None);
             }
-            KeyCode::Char('h') | KeyCode::Char('H') => {
-                app.toggle_dormant();
+            KeyCode::Char(c) => {
+                if let Some(action) = FileAction::ALL.into_iter().find(|a| a.key() == c) {
+                    app.run_file_action(Some(action));
+                }
             }
-
             _ => {}
         }
     }
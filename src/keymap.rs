@@ -0,0 +1,387 @@
+// ============================================================================
+// Keymap - Named actions and the keys bound to them
+// ============================================================================
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::fmt;
+use crate::app::{InputMode, Motion};
+use crate::config::{KeyList, KeysConfig};
+
+/// Everything a key can be bound to. When several actions share a key, the
+/// first one (in this order) that applies in the current mode runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    NormalMode,
+    InsertMode,
+    Append,
+    Quit,
+    QueryOlder,
+    QueryNewer,
+    Next,
+    Previous,
+    Top,
+    Bottom,
+    CursorLeft,
+    CursorRight,
+    CursorStart,
+    CursorEnd,
+    WordForward,
+    WordBackward,
+    WordEnd,
+    ClearInput,
+    Backspace,
+    Delete,
+    DeleteToEnd,
+    ChangeToEnd,
+    DeleteOperator,
+    ChangeOperator,
+    LaunchAsRoot,
+    LaunchAndStay,
+    Launch,
+    ToggleMode,
+    ToggleTui,
+    ToggleDormant,
+    RaiseScore,
+    LowerScore,
+    BrowseUp,
+    Editor,
+    FileMenu,
+    OpenWith,
+    Yank,
+    Mark,
+    Preview,
+    Details,
+}
+
+impl Action {
+    pub const ALL: [Action; 40] = [
+        Action::NormalMode,
+        Action::InsertMode,
+        Action::Append,
+        Action::Quit,
+        Action::QueryOlder,
+        Action::QueryNewer,
+        Action::Next,
+        Action::Previous,
+        Action::Top,
+        Action::Bottom,
+        Action::CursorLeft,
+        Action::CursorRight,
+        Action::CursorStart,
+        Action::CursorEnd,
+        Action::WordForward,
+        Action::WordBackward,
+        Action::WordEnd,
+        Action::ClearInput,
+        Action::Backspace,
+        Action::Delete,
+        Action::DeleteToEnd,
+        Action::ChangeToEnd,
+        Action::DeleteOperator,
+        Action::ChangeOperator,
+        Action::LaunchAsRoot,
+        Action::LaunchAndStay,
+        Action::Launch,
+        Action::ToggleMode,
+        Action::ToggleTui,
+        Action::ToggleDormant,
+        Action::RaiseScore,
+        Action::LowerScore,
+        Action::BrowseUp,
+        Action::Editor,
+        Action::FileMenu,
+        Action::OpenWith,
+        Action::Yank,
+        Action::Mark,
+        Action::Preview,
+        Action::Details,
+    ];
+
+    /// Name used in `[keys.insert]` / `[keys.normal]`
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::NormalMode => "normal_mode",
+            Action::InsertMode => "insert_mode",
+            Action::Append => "append",
+            Action::Quit => "quit",
+            Action::QueryOlder => "query_older",
+            Action::QueryNewer => "query_newer",
+            Action::Next => "next",
+            Action::Previous => "previous",
+            Action::Top => "top",
+            Action::Bottom => "bottom",
+            Action::CursorLeft => "cursor_left",
+            Action::CursorRight => "cursor_right",
+            Action::CursorStart => "cursor_start",
+            Action::CursorEnd => "cursor_end",
+            Action::WordForward => "word_forward",
+            Action::WordBackward => "word_backward",
+            Action::WordEnd => "word_end",
+            Action::ClearInput => "clear_input",
+            Action::Backspace => "backspace",
+            Action::Delete => "delete",
+            Action::DeleteToEnd => "delete_to_end",
+            Action::ChangeToEnd => "change_to_end",
+            Action::DeleteOperator => "delete_operator",
+            Action::ChangeOperator => "change_operator",
+            Action::LaunchAsRoot => "launch_as_root",
+            Action::LaunchAndStay => "launch_and_stay",
+            Action::Launch => "launch",
+            Action::ToggleMode => "toggle_mode",
+            Action::ToggleTui => "toggle_tui",
+            Action::ToggleDormant => "toggle_dormant",
+            Action::RaiseScore => "raise_score",
+            Action::LowerScore => "lower_score",
+            Action::BrowseUp => "browse_up",
+            Action::Editor => "editor",
+            Action::FileMenu => "file_menu",
+            Action::OpenWith => "open_with",
+            Action::Yank => "yank",
+            Action::Mark => "mark",
+            Action::Preview => "preview",
+            Action::Details => "details",
+        }
+    }
+
+    /// What an action means as the motion after `d` or `c`
+    pub fn motion(self) -> Option<Motion> {
+        match self {
+            Action::WordForward => Some(Motion::WordForward),
+            Action::WordBackward => Some(Motion::WordBackward),
+            Action::WordEnd => Some(Motion::WordEnd),
+            Action::CursorStart => Some(Motion::LineStart),
+            Action::CursorEnd => Some(Motion::LineEnd),
+            _ => None,
+        }
+    }
+}
+
+/// Built-in insert mode bindings
+const DEFAULT_INSERT: &[(Action, &[&str])] = &[
+    (Action::NormalMode, &["Esc"]),
+    // Up/Down recall past queries on an empty input, and move the selection otherwise
+    (Action::QueryOlder, &["Up", "Ctrl+r"]),
+    (Action::QueryNewer, &["Down", "Ctrl+s"]),
+    (Action::Next, &["Down", "Ctrl+n"]),
+    (Action::Previous, &["Up", "Ctrl+p"]),
+    (Action::CursorLeft, &["Left", "Ctrl+b"]),
+    (Action::CursorRight, &["Right", "Ctrl+f"]),
+    (Action::CursorStart, &["Home", "Ctrl+a"]),
+    (Action::CursorEnd, &["End", "Ctrl+e"]),
+    (Action::ClearInput, &["Ctrl+u"]),
+    (Action::Backspace, &["Backspace"]),
+    (Action::Delete, &["Delete"]),
+    (Action::LaunchAsRoot, &["Ctrl+Enter", "Shift+Enter"]),
+    (Action::LaunchAndStay, &["Alt+Enter"]),
+    (Action::Launch, &["Enter"]),
+    (Action::ToggleMode, &["Tab"]),
+    (Action::ToggleTui, &["Ctrl+t"]),
+    (Action::ToggleDormant, &["Ctrl+h"]),
+    (Action::FileMenu, &["Ctrl+o"]),
+    (Action::Yank, &["Ctrl+y"]),
+    (Action::Mark, &["Ctrl+Space"]),
+    (Action::Details, &["Ctrl+d"]),
+];
+
+/// Built-in normal mode bindings
+const DEFAULT_NORMAL: &[(Action, &[&str])] = &[
+    (Action::InsertMode, &["i"]),
+    (Action::Append, &["a"]),
+    (Action::Quit, &["q", "Esc"]),
+    (Action::Next, &["j", "Down"]),
+    (Action::Previous, &["k", "Up"]),
+    (Action::Top, &["g"]),
+    (Action::Bottom, &["G"]),
+    (Action::CursorLeft, &["Left"]),
+    (Action::CursorRight, &["Right"]),
+    (Action::CursorStart, &["0", "Home"]),
+    (Action::CursorEnd, &["$", "End"]),
+    (Action::WordForward, &["w"]),
+    (Action::WordBackward, &["b"]),
+    (Action::WordEnd, &["e"]),
+    (Action::Delete, &["x", "Delete"]),
+    (Action::DeleteToEnd, &["D"]),
+    (Action::ChangeToEnd, &["C"]),
+    (Action::DeleteOperator, &["d"]),
+    (Action::ChangeOperator, &["c"]),
+    (Action::LaunchAsRoot, &["Ctrl+Enter", "Shift+Enter"]),
+    (Action::LaunchAndStay, &["Alt+Enter"]),
+    (Action::Launch, &["Enter"]),
+    (Action::ToggleMode, &["Tab"]),
+    (Action::ToggleTui, &["Ctrl+t"]),
+    (Action::ToggleDormant, &["h", "H"]),
+    (Action::RaiseScore, &["+", "="]),
+    // `-` lowers the score in Apps mode and goes up a directory elsewhere
+    (Action::LowerScore, &["-"]),
+    (Action::BrowseUp, &["Backspace", "-"]),
+    (Action::Editor, &["E"]),
+    (Action::FileMenu, &["o"]),
+    (Action::OpenWith, &["O"]),
+    (Action::Yank, &["y"]),
+    (Action::Mark, &["Space"]),
+    (Action::Preview, &["p"]),
+    (Action::Details, &["K"]),
+];
+
+/// One key with its modifiers, e.g. "Ctrl+Enter"
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeyBinding {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeyBinding {
+    /// Parse "Ctrl+Alt+x", "Shift+Enter", "Space", "G", "+" and the like
+    pub fn parse(text: &str) -> Option<Self> {
+        let (mods, key) = if text == "+" {
+            ("", "+")
+        } else if let Some(mods) = text.strip_suffix("++") {
+            (mods, "+")
+        } else {
+            text.rsplit_once('+').unwrap_or(("", text))
+        };
+
+        let mut modifiers = KeyModifiers::NONE;
+        for name in mods.split('+').filter(|m| !m.is_empty()) {
+            modifiers |= match name.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return None,
+            };
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" => KeyCode::PageUp,
+                "pagedown" => KeyCode::PageDown,
+                "space" => KeyCode::Char(' '),
+                name => KeyCode::F(name.strip_prefix('f')?.parse().ok()?),
+            },
+        };
+        Some(Self::normalized(code, modifiers))
+    }
+
+    fn from_event(key: &KeyEvent) -> Self {
+        let modifiers = key.modifiers & (KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT);
+        Self::normalized(key.code, modifiers)
+    }
+
+    /// Shifted characters are matched by the character they produce, so "G"
+    /// and "Shift+g" are the same binding whatever the terminal reports
+    fn normalized(code: KeyCode, mut modifiers: KeyModifiers) -> Self {
+        let code = match code {
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::BackTab => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::BackTab
+            }
+            code => code,
+        };
+        Self { code, modifiers }
+    }
+}
+
+impl fmt::Display for KeyBinding {
+    /// Same spelling the config uses, e.g. "Ctrl+Space"
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (modifier, name) in [
+            (KeyModifiers::CONTROL, "Ctrl+"),
+            (KeyModifiers::ALT, "Alt+"),
+            (KeyModifiers::SHIFT, "Shift+"),
+        ] {
+            if self.modifiers.contains(modifier) {
+                f.write_str(name)?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            KeyCode::PageUp => f.write_str("PageUp"),
+            KeyCode::PageDown => f.write_str("PageDown"),
+            code => write!(f, "{:?}", code),
+        }
+    }
+}
+
+type Bindings = Vec<(Action, Vec<KeyBinding>)>;
+
+pub struct Keymap {
+    insert: Bindings,
+    normal: Bindings,
+}
+
+impl Keymap {
+    /// The default map with `[keys.*]` entries replacing the bindings of the
+    /// actions they name. Unknown actions and unparsable keys are ignored.
+    pub fn from_config(config: &KeysConfig) -> Self {
+        Self {
+            insert: bindings(DEFAULT_INSERT, &config.insert),
+            normal: bindings(DEFAULT_NORMAL, &config.normal),
+        }
+    }
+
+    /// First key bound to `action` in `mode`, for hints
+    pub fn key_for(&self, mode: InputMode, action: Action) -> Option<KeyBinding> {
+        let bindings = match mode {
+            InputMode::Insert => &self.insert,
+            InputMode::Normal => &self.normal,
+        };
+        bindings
+            .iter()
+            .find(|(a, _)| *a == action)
+            .and_then(|(_, keys)| keys.first().copied())
+    }
+
+    /// Actions bound to `key` in `mode`, in priority order
+    pub fn actions(&self, mode: InputMode, key: &KeyEvent) -> impl Iterator<Item = Action> + '_ {
+        let pressed = KeyBinding::from_event(key);
+        let bindings = match mode {
+            InputMode::Insert => &self.insert,
+            InputMode::Normal => &self.normal,
+        };
+        bindings
+            .iter()
+            .filter(move |(_, keys)| keys.contains(&pressed))
+            .map(|(action, _)| *action)
+    }
+}
+
+fn bindings(defaults: &[(Action, &[&str])], overrides: &HashMap<String, KeyList>) -> Bindings {
+    Action::ALL
+        .into_iter()
+        .filter_map(|action| {
+            let keys: Vec<KeyBinding> = match overrides.get(action.name()) {
+                Some(list) => list.keys().iter().filter_map(|k| KeyBinding::parse(k)).collect(),
+                None => defaults
+                    .iter()
+                    .find(|(a, _)| *a == action)?
+                    .1
+                    .iter()
+                    .filter_map(|k| KeyBinding::parse(k))
+                    .collect(),
+            };
+            (!keys.is_empty()).then_some((action, keys))
+        })
+        .collect()
+}
@@ -0,0 +1,21 @@
+// ============================================================================
+// Logging - --debug traces to ~/.cache/rula/rula.log
+// ============================================================================
+
+use std::fs::OpenOptions;
+use std::io;
+use std::sync::Mutex;
+use crate::system::get_cache_path;
+
+/// Send tracing events to the log file. Without this there is no subscriber
+/// and the tracing macros are close to free.
+pub fn init() -> io::Result<()> {
+    let file = OpenOptions::new().create(true).append(true).open(get_cache_path("rula.log"))?;
+    tracing_subscriber::fmt()
+        .with_writer(Mutex::new(file))
+        .with_ansi(false)
+        .with_max_level(tracing::Level::DEBUG)
+        .init();
+    tracing::info!(version = env!("CARGO_PKG_VERSION"), "debug logging started");
+    Ok(())
+}
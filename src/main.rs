@@ -3,24 +3,44 @@
 // ============================================================================
 
 mod app;
+mod bench;
+mod config;
 mod db;
+mod error;
+mod icons;
+mod index;
 mod input;
+mod keymap;
+mod logging;
+mod plugin;
+mod report;
+mod stats;
 mod system;
 mod terminal;
+#[cfg(test)]
+mod tests;
 mod theme;
 mod ui;
+mod watcher;
 
 use std::env;
 use std::process::{Command, Stdio};
 use std::os::unix::process::CommandExt;
-use crossterm::terminal::{disable_raw_mode, enable_raw_mode};
 use app::App;
-use input::InputHandler;
+use config::LaunchScope;
+use error::Error;
+use input::{Input, InputHandler};
+use terminal::RawModeGuard;
 use ui::Ui;
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() -> error::Result<()> {
     // Check for seed flag
     let args: Vec<String> = env::args().collect();
+
+    // Trace scans, searches and failures to a log file instead of dropping them
+    if args.iter().any(|arg| arg == "--debug") {
+        logging::init()?;
+    }
     if args.len() > 1 && args[1] == "--seed" {
         let db = db::Database::new()?;
         system::seed_database(&db);
@@ -28,20 +48,108 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         return Ok(());
     }
 
+    // Seed from Homebrew/Linuxbrew formulae
+    if args.len() > 1 && args[1] == "--seed-brew" {
+        let db = db::Database::new()?;
+        system::seed_database_brew(&db);
+        println!("Done! Now run the launcher normally.");
+        return Ok(());
+    }
+
     // Rebuild app cache flag
     if args.len() > 1 && args[1] == "--rebuild-cache" {
         let db = db::Database::new()?;
-        system::rebuild_app_cache(&db)?;
+        system::rebuild_app_cache(&db, &config::Config::load()?.apps)?;
+        system::rebuild_project_cache(&config::Config::load()?.projects)?;
         println!("Cache rebuilt successfully!");
         return Ok(());
     }
 
-    enable_raw_mode()?;
+    // Usage statistics dashboard
+    if args.len() > 1 && args[1] == "--stats" {
+        let db = db::Database::new()?;
+        stats::print_stats(&db, &config::Config::load()?);
+        return Ok(());
+    }
+
+    // Time scans, cache loads and searches on this machine
+    if args.len() > 1 && args[1] == "--bench" {
+        let db = db::Database::new()?;
+        bench::run(&db, &config::Config::load()?);
+        return Ok(());
+    }
+
+    // Forget uninstalled apps that haven't been launched for a while
+    if args.len() > 1 && args[1] == "--prune" {
+        let idle_days = args.get(2).and_then(|d| d.parse().ok()).unwrap_or(90);
+        let db = db::Database::new()?;
+        system::prune_database(&db, &config::Config::load()?, idle_days);
+        return Ok(());
+    }
+
+    // Give dormant apps a fresh start instead of hiding them
+    if args.len() > 1 && args[1] == "--reset-dormant" {
+        let db = db::Database::new()?;
+        system::reset_dormant(&db, &config::Config::load()?);
+        return Ok(());
+    }
+
+    // Back up or restore preferences and usage history as JSON
+    if args.len() > 2 && args[1] == "--export" {
+        let db = db::Database::new()?;
+        let json = serde_json::to_string_pretty(&db.export_json()?)?;
+        std::fs::write(&args[2], json + "\n")?;
+        println!("Exported to {}", args[2]);
+        return Ok(());
+    }
+    if args.len() > 2 && args[1] == "--import" {
+        let db = db::Database::new()?;
+        let data: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&args[2])?)?;
+        let rows = db.import_json(&data)?;
+        println!("Imported {} rows from {}", rows, args[2]);
+        return Ok(());
+    }
 
-    // Fast startup - only load cached apps, files are lazy-loaded
-    let mut app = App::new();
-    let mut ui = Ui::new()?;
-    let input_handler = InputHandler::new();
+    // Daemon mode: keep app cache and file index fresh via inotify
+    if args.len() > 1 && args[1] == "--watch" {
+        let config = config::Config::load()?;
+        let scope = index::SearchScope::from_config(&config.files);
+        watcher::run_daemon(&scope, &config.apps)?;
+        return Ok(());
+    }
+
+    // Build or incrementally refresh the persistent file index
+    if args.len() > 1 && args[1] == "--index" {
+        let scope = index::SearchScope::from_config(&config::Config::load()?.files);
+        let index = index::update_file_index(&scope)?;
+        println!("Indexed {} files.", index.paths().len());
+        return Ok(());
+    }
+
+    // Pick a palette for this session over the config's `theme`
+    let theme_override = match args.iter().position(|arg| arg == "--theme") {
+        Some(i) => match args.get(i + 1) {
+            Some(name) if theme::Theme::load(name).is_some() => Some(name.clone()),
+            _ => {
+                let names: Vec<&str> = theme::Theme::BUILTIN.iter().map(|(name, _)| *name).collect();
+                return Err(Error::Usage(format!("--theme takes one of: {}, pywal, base16:<file>", names.join(", "))));
+            }
+        },
+        None => None,
+    };
+
+    // Dropped last, after the Ui has left the alternate screen
+    let raw_mode = RawModeGuard::new()?;
+
+    // Fast startup - cached apps now, fresh scan in the background
+    let mut app = App::new()?;
+    app.private = args.iter().any(|arg| arg == "--private");
+    if let Some(name) = theme_override {
+        app.config.theme = name;
+    }
+    let mut ui = Ui::new(&app.config)?;
+    let input_handler = InputHandler::new(&app.config.keys);
+    let mut events = db::EventLog::spawn();
 
     let mut should_render = true;
 
@@ -56,27 +164,125 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
         }
 
         if app.should_launch {
-            if let Some((program, args, is_tui)) = app.launch_command.take() {
-                disable_raw_mode()?;
-                spawn_detached(&program, &args, is_tui);
-                break;
+            app.should_launch = false;
+            let launches = app.take_launches();
+            if !launches.is_empty() {
+                let stay = app.config.launch.keep_open || app.stay_after_launch;
+                app.stay_after_launch = false;
+
+                // Spawn before leaving the TUI so a missing binary can be reported
+                // instead of rula silently exiting
+                let mut failure = None;
+                for ((program, args, is_tui), launched_app) in launches {
+                    let scope = app.config.launch.scope_for(launched_app.as_deref());
+                    let spawned = spawn_detached(&program, &args, is_tui, scope);
+                    if !app.private {
+                        events.record(db::LaunchEvent {
+                            app: launched_app.clone().unwrap_or_else(|| program.clone()),
+                            mode: app.mode_name(),
+                            success: spawned.is_ok(),
+                            launched_at: unix_now(),
+                        });
+                    }
+                    if let Err(e) = spawned {
+                        tracing::warn!("{}", e);
+                        failure = Some(e.to_string());
+                        continue;
+                    }
+                    if let Some(workspace) = app.config.launch.workspace_for(launched_app.as_deref()) {
+                        system::switch_workspace(workspace);
+                    }
+                    if !app.private {
+                        if let Err(e) = app.db.record_launch(&program, &args, is_tui) {
+                            tracing::warn!("recording launch of {} failed: {}", program, e);
+                        }
+                    }
+                }
+                if failure.is_some() {
+                    app.launch_error = failure;
+                    should_render = true;
+                    continue;
+                }
+                if !app.private {
+                    if let Err(e) = app.db.record_query(&app.mode_name(), &app.input) {
+                        tracing::warn!("recording query failed: {}", e);
+                    }
+                }
+
+                // The child is detached with null stdio, so raw mode can stay on
+                // when the launcher keeps running
+                if !stay {
+                    restore_terminal(ui, raw_mode);
+                    events.finish();
+                    return Ok(());
+                }
+                should_render = true;
             }
         }
 
-        // Poll with long timeout to prevent busy-waiting
-        if let Some(key) = input_handler.poll(100_000) {
-            input_handler.process(&mut app, key);
+        if app.poll_app_scan() {
+            should_render = true;
+        }
+        if app.poll_search() {
+            should_render = true;
+        }
+        if app.poll_preview() {
+            should_render = true;
+        }
+        if app.expire_flash() {
             should_render = true;
         }
+
+        // Poll with long timeout to prevent busy-waiting, shorter while background
+        // work may deliver results
+        let mut timeout = if let Some(delay) = app.search_delay() {
+            delay.as_millis() as u64
+        } else if app.is_searching() {
+            10
+        } else if app.is_scanning() {
+            50
+        } else if app.is_watching() {
+            500
+        } else {
+            100_000
+        };
+        if let Some(left) = app.flash_remaining() {
+            timeout = timeout.min(left.as_millis() as u64);
+        }
+        match input_handler.poll(timeout) {
+            Some(Input::Key(key)) => {
+                input_handler.process(&mut app, key);
+                should_render = true;
+            }
+            Some(Input::Resize(width, height)) => {
+                ui.resize(width, height);
+                should_render = true;
+            }
+            None => {}
+        }
     }
 
-    disable_raw_mode()?;
+    restore_terminal(ui, raw_mode);
+    events.finish();
     Ok(())
 }
 
-fn spawn_detached(program: &str, args: &[String], is_tui: bool) {
-    let final_program: String;
-    let final_args: Vec<String>;
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// Leave the alternate screen, then raw mode, before exiting or handing off
+fn restore_terminal(ui: Ui, raw_mode: RawModeGuard) {
+    drop(ui);
+    drop(raw_mode);
+}
+
+fn spawn_detached(program: &str, args: &[String], is_tui: bool, scope: LaunchScope) -> error::Result<()> {
+    let mut final_program: String;
+    let mut final_args: Vec<String>;
 
     if is_tui {
         final_program = "kitty".to_string();
@@ -88,10 +294,34 @@ fn spawn_detached(program: &str, args: &[String], is_tui: bool) {
         final_args = args.to_vec();
     }
 
+    // Give the program its own cgroup so OOM kills and session cleanup don't
+    // treat it as part of rula's terminal. Skipped if the tool isn't installed.
+    let scope_prefix: &[&str] = match scope {
+        LaunchScope::Systemd => &["systemd-run", "--user", "--scope", "--slice=app.slice", "--quiet", "--"],
+        LaunchScope::Uwsm => &["uwsm", "app", "--"],
+        LaunchScope::None => &[],
+    };
+    if let Some((tool, tool_args)) = scope_prefix.split_first() {
+        if system::is_on_path(tool) {
+            // The wrapper would start and then fail on its own, out of sight
+            if !system::is_on_path(&final_program) {
+                return Err(Error::Launch {
+                    program: final_program,
+                    source: std::io::Error::new(std::io::ErrorKind::NotFound, "not found in PATH"),
+                });
+            }
+            let mut wrapped: Vec<String> = tool_args.iter().map(|a| a.to_string()).collect();
+            wrapped.push(final_program);
+            wrapped.extend(final_args);
+            final_program = tool.to_string();
+            final_args = wrapped;
+        }
+    }
+
     let args_refs: Vec<&str> = final_args.iter().map(|s| s.as_str()).collect();
 
-    unsafe {
-        let _ = Command::new(&final_program)
+    let child = unsafe {
+        Command::new(&final_program)
             .args(&args_refs)
             .stdin(Stdio::null())
             .stdout(Stdio::null())
@@ -100,6 +330,12 @@ fn spawn_detached(program: &str, args: &[String], is_tui: bool) {
                 libc::setsid();
                 Ok(())
             })
-            .spawn();
-    }
+            .spawn()
+    };
+
+    // Reap it in the background so a launcher that stays open leaves no zombies
+    let mut child = child.map_err(|source| Error::Launch { program: final_program.clone(), source })?;
+    tracing::debug!(program = %final_program, args = ?final_args, "spawned");
+    std::thread::spawn(move || child.wait());
+    Ok(())
 }
@@ -0,0 +1,96 @@
+// ============================================================================
+// Plugins - External executables providing custom modes over JSON lines
+// ============================================================================
+
+use serde::Deserialize;
+use std::io::{BufRead, BufReader};
+use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+
+use crate::config::PluginConfig;
+
+/// Output beyond this many items is ignored
+const MAX_ITEMS: usize = 200;
+
+/// One result line printed by a plugin, e.g.
+/// `{"title": "Buy milk", "subtitle": "todo", "action": "done 3"}`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct PluginItem {
+    pub title: String,
+    pub subtitle: String,
+    /// Nerd font glyph shown before the title
+    pub icon: String,
+    /// Command launched on Enter
+    pub exec: String,
+    /// Run `exec` inside a terminal
+    pub terminal: bool,
+    /// Handed back to the plugin as `--action <value>` on Enter, when there is no `exec`
+    pub action: String,
+}
+
+/// The plugin's command line, split into program and arguments
+pub fn plugin_command(plugin: &PluginConfig) -> Option<(String, Vec<String>)> {
+    let mut words = shell_words::split(&plugin.command).ok()?.into_iter();
+    let program = words.next()?;
+    Some((program, words.collect()))
+}
+
+/// Run the plugin with `query` as its last argument, publishing the items parsed
+/// so far after every line. Lines that aren't valid JSON items are skipped.
+pub fn run_plugin(plugin: &PluginConfig, query: &str, cancel: &AtomicBool, tx: &Sender<Vec<PluginItem>>) {
+    let (program, mut args) = match plugin_command(plugin) {
+        Some(command) => command,
+        None => return,
+    };
+    args.push(query.to_string());
+
+    let mut child = match Command::new(&program)
+        .args(&args)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+    {
+        Ok(child) => child,
+        Err(e) => {
+            tracing::warn!("plugin {}: running {} failed: {}", plugin.name, program, e);
+            return;
+        }
+    };
+
+    let mut items = Vec::new();
+    if let Some(stdout) = child.stdout.take() {
+        for line in BufReader::new(stdout).lines() {
+            // A newer query superseded this one - don't wait for the rest
+            if cancel.load(Ordering::Relaxed) {
+                let _ = child.kill();
+                break;
+            }
+            let line = match line {
+                Ok(line) => line,
+                Err(_) => break,
+            };
+
+            let item = match serde_json::from_str::<PluginItem>(&line) {
+                Ok(item) => item,
+                Err(e) => {
+                    tracing::debug!("plugin {}: skipped line {:?}: {}", plugin.name, line, e);
+                    continue;
+                }
+            };
+            items.push(item);
+            if tx.send(items.clone()).is_err() || items.len() >= MAX_ITEMS {
+                let _ = child.kill();
+                break;
+            }
+        }
+    }
+    let _ = child.wait();
+
+    // Always publish the final list, even when the plugin printed nothing
+    if !cancel.load(Ordering::Relaxed) {
+        let _ = tx.send(items);
+    }
+}
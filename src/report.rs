@@ -0,0 +1,37 @@
+// ============================================================================
+// Reports - Themed text output shared by `rula --stats` and `rula --bench`
+// ============================================================================
+
+use std::io::IsTerminal;
+use crate::config::Config;
+use crate::theme::*;
+
+/// Printed line by line instead of drawn through `Terminal`, so a report
+/// scrolls, pipes and redirects like any other command's output.
+/// Colors are left out when stdout isn't a terminal.
+pub struct Report {
+    pub theme: Theme,
+    color: bool,
+}
+
+impl Report {
+    pub fn new(config: &Config) -> Self {
+        Self {
+            theme: Theme::from_config(config),
+            color: std::io::stdout().is_terminal(),
+        }
+    }
+
+    pub fn paint(&self, style: Style, text: &str) -> String {
+        if self.color {
+            style.apply(text)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Section title, set off by a blank line
+    pub fn heading(&self, text: &str) {
+        println!("\n{}", self.paint(Style::new().fg(self.theme.love).bold(), text));
+    }
+}
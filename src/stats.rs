@@ -0,0 +1,128 @@
+// ============================================================================
+// Usage Statistics - `rula --stats` dashboard
+// ============================================================================
+
+use std::time::{SystemTime, UNIX_EPOCH};
+use crate::config::Config;
+use crate::db::Database;
+use crate::report::Report;
+use crate::theme::*;
+use crate::ui::{format_age, format_date};
+
+const DAY_SECS: u64 = 24 * 60 * 60;
+const WEEK_SECS: u64 = 7 * DAY_SECS;
+const BAR_WIDTH: usize = 40;
+
+/// Print most-launched apps, launch activity and dormant candidates
+pub fn print_stats(db: &Database, config: &Config) {
+    let report = Report::new(config);
+    let theme = &report.theme;
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    report.heading("Most launched");
+    let top = db.top_apps(10);
+    let name_width = top.iter().map(|(name, _)| name.chars().count()).max().unwrap_or(0);
+    let max = top.first().map(|(_, usage)| *usage).unwrap_or(0);
+    for (name, usage) in &top {
+        println!(
+            "  {:<width$}  {} {}",
+            name,
+            report.paint(Style::new().fg(theme.foam), &bar(*usage as u64, max as u64)),
+            usage,
+            width = name_width,
+        );
+    }
+    if top.is_empty() {
+        println!("  {}", report.paint(Style::new().fg(theme.muted), "Nothing launched yet"));
+    }
+
+    // Buckets are aligned to UTC days, so today's bar is the partial one
+    let today = now / DAY_SECS * DAY_SECS;
+    let launches = db.launch_times_since(today + DAY_SECS - 8 * WEEK_SECS);
+
+    report.heading("Launches per day");
+    let days: Vec<(String, Bucket)> = (0..14)
+        .rev()
+        .map(|i| {
+            let start = today - i * DAY_SECS;
+            (format_date(start), Bucket::count(&launches, start, DAY_SECS))
+        })
+        .collect();
+    print_histogram(&report, &days);
+
+    report.heading("Launches per week");
+    let weeks: Vec<(String, Bucket)> = (0..8)
+        .rev()
+        .map(|i| {
+            let start = today + DAY_SECS - (i + 1) * WEEK_SECS;
+            (format_date(start), Bucket::count(&launches, start, WEEK_SECS))
+        })
+        .collect();
+    print_histogram(&report, &weeks);
+
+    // Same window the launcher uses to hide apps as dormant
+    report.heading(&format!("Dormant candidates (unused for {}+ days)", config.apps.dormant_days));
+    let idle = db.idle_apps(now.saturating_sub(config.apps.dormant_secs()), 15);
+    for (name, last_used) in &idle {
+        println!(
+            "  {}  {}",
+            name,
+            report.paint(Style::new().fg(theme.muted), &format!("last used {}", format_age(now.saturating_sub(*last_used)))),
+        );
+    }
+    if idle.is_empty() {
+        println!("  {}", report.paint(Style::new().fg(theme.muted), "None"));
+    }
+    println!();
+}
+
+/// Launch attempts in one histogram bucket
+struct Bucket {
+    launches: u64,
+    failed: u64,
+}
+
+impl Bucket {
+    fn count(launches: &[(u64, bool)], start: u64, len: u64) -> Self {
+        let attempts: Vec<bool> = launches
+            .iter()
+            .filter(|(at, _)| *at >= start && *at < start + len)
+            .map(|(_, success)| *success)
+            .collect();
+        Self {
+            launches: attempts.len() as u64,
+            failed: attempts.iter().filter(|success| !**success).count() as u64,
+        }
+    }
+}
+
+fn print_histogram(report: &Report, rows: &[(String, Bucket)]) {
+    let theme = &report.theme;
+    let max = rows.iter().map(|(_, bucket)| bucket.launches).max().unwrap_or(0);
+    for (label, bucket) in rows {
+        let failed = if bucket.failed > 0 {
+            format!(" {}", report.paint(Style::new().fg(theme.love), &format!("({} failed)", bucket.failed)))
+        } else {
+            String::new()
+        };
+        println!(
+            "  {}  {} {}{}",
+            report.paint(Style::new().fg(theme.subtle), label),
+            report.paint(Style::new().fg(theme.gold), &bar(bucket.launches, max)),
+            bucket.launches,
+            failed,
+        );
+    }
+}
+
+/// `value` as a bar of up to BAR_WIDTH cells, scaled against `max`
+fn bar(value: u64, max: u64) -> String {
+    if max == 0 {
+        return String::new();
+    }
+    let cells = (value as usize * BAR_WIDTH).div_ceil(max as usize);
+    "█".repeat(cells)
+}
@@ -2,94 +2,267 @@
 // System Scanner - Optimized with Caching and Lazy Loading
 // ============================================================================
 
-use freedesktop_entry_parser::parse_entry;
-use fuzzy_matcher::skim::SkimMatcherV2;
-use fuzzy_matcher::FuzzyMatcher;
-use std::collections::HashSet;
+use freedesktop_entry_parser::{parse_entry, Section};
+use nucleo_matcher::pattern::{AtomKind, CaseMatching, Normalization, Pattern};
+use nucleo_matcher::{Matcher, Utf32Str};
+use regex::{Regex, RegexBuilder};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
 use std::env;
 use std::fs;
 use std::io;
 use std::os::unix::fs::PermissionsExt;
 use std::path::{Path, PathBuf};
 use std::process::Command;
-use std::time::{SystemTime, UNIX_EPOCH};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver, Sender, TryRecvError};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use walkdir::WalkDir;
 
-use crate::db::Database;
+use crate::config::{expand_tilde, AppsConfig, Config, ProjectsConfig};
+use crate::db::{Database, HistoryEntry};
+use crate::index::{update_file_index, FileIndex, SearchScope};
+use crate::watcher::DirWatcher;
 
 #[derive(Clone, Debug)]
 pub struct AppEntry {
     pub name: String,
+    /// Localized `GenericName=`, e.g. "Web Browser"
+    pub generic_name: String,
+    /// Localized `Keywords=`, e.g. ["Internet", "WWW", "Browser"]
+    pub keywords: Vec<String>,
+    /// Localized `Comment=` tooltip text
+    pub comment: String,
+    /// `Categories=`, e.g. ["Network", "WebBrowser"]
+    pub categories: Vec<String>,
     pub exec: String,
     pub is_cli_only: bool,
+    /// A `[Desktop Action]` of another entry, e.g. "Firefox › New Private Window"
+    pub is_action: bool,
+    /// `Terminal=true` in the desktop file
+    pub is_terminal: bool,
+    /// `Icon=` value, substituted for the %i field code
+    pub icon: String,
+    /// Source .desktop file, empty for $PATH executables
+    pub desktop_path: String,
+    /// Flatpak application ID (`X-Flatpak=`), empty for native apps
+    pub flatpak_id: String,
     pub total_score: i32,
     pub is_dormant: bool,
 }
 
+impl AppEntry {
+    /// Whether to run in a terminal when the user has no stored preference
+    pub fn default_tui(&self) -> bool {
+        self.is_cli_only || self.is_terminal
+    }
+
+    /// Split the Exec line and expand its field codes (Desktop Entry spec).
+    /// `files` fills %f/%F/%u/%U; without files those codes simply disappear,
+    /// and an Exec line with no file code gets the files appended instead.
+    pub fn exec_args(&self, files: &[String]) -> Vec<String> {
+        // Sandboxed apps go through flatpak, which forwards files into the sandbox itself
+        if !self.flatpak_id.is_empty() {
+            let mut args = vec!["flatpak".to_string(), "run".to_string()];
+            if files.is_empty() {
+                args.push(self.flatpak_id.clone());
+            } else {
+                args.push("--file-forwarding".to_string());
+                args.push(self.flatpak_id.clone());
+                args.push("@@".to_string());
+                args.extend(files.iter().cloned());
+                args.push("@@".to_string());
+            }
+            return args;
+        }
+
+        // Quoting follows shell double-quote rules, so split before expanding
+        // to keep substituted paths with spaces as a single argument
+        let tokens = shell_words::split(&self.exec).unwrap_or_default();
+        let mut args = Vec::with_capacity(tokens.len());
+        let mut takes_files = false;
+
+        for token in tokens {
+            // List and icon codes are only valid as standalone arguments
+            match token.as_str() {
+                // Flatpak file-forwarding markers (left in exported action Exec lines)
+                "@@" | "@@u" | "@@f" => continue,
+                "%F" | "%U" => {
+                    takes_files = true;
+                    args.extend(files.iter().cloned());
+                    continue;
+                }
+                "%i" => {
+                    if !self.icon.is_empty() {
+                        args.push("--icon".to_string());
+                        args.push(self.icon.clone());
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+
+            let mut expanded = String::with_capacity(token.len());
+            let mut had_code = false;
+            let mut chars = token.chars();
+
+            while let Some(c) = chars.next() {
+                if c != '%' {
+                    expanded.push(c);
+                    continue;
+                }
+                match chars.next() {
+                    Some('%') => expanded.push('%'),
+                    Some('f' | 'u' | 'F' | 'U') => {
+                        had_code = true;
+                        takes_files = true;
+                        if let Some(file) = files.first() {
+                            expanded.push_str(file);
+                        }
+                    }
+                    Some('i') => expanded.push_str(&self.icon),
+                    Some('c') => expanded.push_str(&self.name),
+                    Some('k') => expanded.push_str(&self.desktop_path),
+                    // Deprecated (%d %D %n %N %v %m) and unknown codes are dropped
+                    _ => had_code = true,
+                }
+            }
+
+            // A lone code with nothing to substitute must not leave an empty argument
+            if expanded.is_empty() && had_code {
+                continue;
+            }
+            args.push(expanded);
+        }
+
+        // Aliases, config entries and CLI tools rarely declare a file code,
+        // but "Open with" should still hand them the file
+        if !takes_files {
+            args.extend(files.iter().cloned());
+        }
+
+        args
+    }
+}
+
 // ============================================================================
 // APP SCANNING WITH CACHE
 // ============================================================================
 
-/// Load apps from cache or rescan if cache is stale
-pub fn scan_apps(db: &Database) -> Vec<AppEntry> {
-    // Try to load from cache first
-    if let Ok(cached) = load_app_cache() {
-        if !cached.is_empty() {
-            return enrich_apps_with_db_data(cached, db);
+/// Load apps from cache only - empty on a cold start, never touches the disk scan
+pub fn load_cached_apps(db: &Database, config: &AppsConfig) -> Vec<AppEntry> {
+    match load_app_cache() {
+        Ok(cached) => enrich_apps_with_db_data(cached, db, config),
+        Err(e) => {
+            tracing::debug!("no usable app cache: {}", e);
+            Vec::new()
         }
     }
+}
 
-    // Cache miss - do full scan and rebuild cache
-    let apps = scan_apps_fresh(db);
-    let _ = save_app_cache(&apps);
-    apps
+/// Run a fresh scan on a background thread, refreshing the cache when done, then
+/// keep watching the application dirs and rescan whenever they change.
+/// The receiver yields each new app list; it disconnects if scanning or watching fails.
+pub fn spawn_app_scan(config: AppsConfig) -> Receiver<Vec<AppEntry>> {
+    let (tx, rx) = mpsc::channel();
+
+    thread::spawn(move || {
+        // rusqlite connections can't be shared across threads, so open our own
+        let db = match Database::new() {
+            Ok(db) => db,
+            Err(e) => {
+                tracing::warn!("app scan: opening database failed: {}", e);
+                return;
+            }
+        };
+
+        let apps = scan_apps_fresh(&db, &config);
+        save_app_cache_logged(&apps);
+        if tx.send(apps).is_err() {
+            return;
+        }
+
+        // Keep an open launcher in sync with installs and removals
+        let mut watcher = match DirWatcher::new() {
+            Ok(w) => w,
+            Err(e) => {
+                tracing::warn!("app scan: watching application dirs failed: {}", e);
+                return;
+            }
+        };
+        watcher.set_dirs(application_dirs());
+
+        while let Some(changed) = watcher.wait_for_changes() {
+            if changed.is_empty() {
+                continue;
+            }
+
+            let apps = scan_apps_fresh(&db, &config);
+            save_app_cache_logged(&apps);
+            if tx.send(apps).is_err() {
+                return;
+            }
+        }
+    });
+
+    rx
+}
+
+/// A failed cache write only costs the next cold start, so it is logged, not fatal
+fn save_app_cache_logged(apps: &[AppEntry]) {
+    if let Err(e) = save_app_cache(apps) {
+        tracing::warn!("saving app cache failed: {}", e);
+    }
 }
 
 /// Force rebuild the app cache
-pub fn rebuild_app_cache(db: &Database) -> io::Result<()> {
-    let apps = scan_apps_fresh(db);
+pub fn rebuild_app_cache(db: &Database, config: &AppsConfig) -> io::Result<()> {
+    let apps = scan_apps_fresh(db, config);
     save_app_cache(&apps)?;
     Ok(())
 }
 
-fn scan_apps_fresh(db: &Database) -> Vec<AppEntry> {
+pub fn scan_apps_fresh(db: &Database, config: &AppsConfig) -> Vec<AppEntry> {
+    let started = Instant::now();
     let mut apps = Vec::new();
     let mut seen_names = HashSet::new();
+    let mut seen_ids = HashSet::new();
     let mut known_execs = HashSet::new();
 
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    let thirty_days = 30 * 24 * 60 * 60;
-
     // OPTIMIZATION: Batch load all DB data in one query (eliminates N+1 problem)
-    let db_data = db.get_all_app_data();
+    let scores = AppScores::load(db, config);
+    let locales = locale_candidates();
+    let score_for = |name: &str| scores.get(name);
 
     // Scan .desktop files
-    let dirs = [
-        "/usr/share/applications",
-        "/usr/local/share/applications",
-        "/home/linuxbrew/.linuxbrew/share/applications",
-    ];
-
-    let home_apps = dirs::home_dir().map(|h| h.join(".local/share/applications"));
-    let mut search_dirs: Vec<PathBuf> = dirs.iter().map(PathBuf::from).collect();
-    if let Some(h) = home_apps {
-        search_dirs.push(h);
-    }
-
-    for dir in search_dirs {
+    for dir in application_dirs() {
         if !dir.exists() {
             continue;
         }
 
-        for entry in WalkDir::new(dir)
-            .max_depth(1)
+        // Nix profiles are symlink farms pointing into /nix/store
+        for entry in WalkDir::new(&dir)
+            .max_depth(3)
+            .follow_links(true)
             .into_iter()
             .filter_map(|e| e.ok())
         {
-            if entry.path().extension().map_or(false, |e| e == "desktop") {
+            if entry.path().extension().is_some_and(|e| e == "desktop") {
+                // Desktop file ID: path below the applications dir with '/' -> '-'
+                let desktop_id = entry
+                    .path()
+                    .strip_prefix(&dir)
+                    .map(|p| p.to_string_lossy().replace('/', "-"))
+                    .unwrap_or_default();
+
+                // Dirs arrive in precedence order, so the first file with an ID wins -
+                // including user overrides that exist only to hide a system entry
+                if !seen_ids.insert(desktop_id) {
+                    continue;
+                }
+
                 if let Ok(entry_file) = parse_entry(entry.path()) {
                     // 1. Get the section safely. If missing, skip this file.
                     let section = match entry_file.section("Desktop Entry") {
@@ -97,25 +270,57 @@ fn scan_apps_fresh(db: &Database) -> Vec<AppEntry> {
                         None => continue,
                     };
 
-                    // 2. Handle NoDisplay (attr returns a list now, take the first item)
-                    let no_display = section
-                        .attr("NoDisplay")
-                        .first() // Get Option<&String>
-                        .map(|s| s == "true")
-                        .unwrap_or(false);
+                    // 2. Handle NoDisplay/Hidden (attr returns a list now, take the first item)
+                    let is_true = |key: &str| {
+                        section
+                            .attr(key)
+                            .first() // Get Option<&String>
+                            .map(|s| s == "true")
+                            .unwrap_or(false)
+                    };
 
-                    if no_display {
+                    if is_true("NoDisplay") || is_true("Hidden") {
                         continue;
                     }
 
-                    // 3. Handle Name
-                    let name = section
-                        .attr("Name")
+                    // 3. Handle Name (best match for the user's locale)
+                    let name = localized_attr(section, "Name", &locales)
+                        .unwrap_or_else(|| "Unknown".to_string());
+                    let generic_name =
+                        localized_attr(section, "GenericName", &locales).unwrap_or_default();
+                    let keywords: Vec<String> = localized_attr(section, "Keywords", &locales)
+                        .unwrap_or_default()
+                        .split(';')
+                        .filter(|k| !k.is_empty())
+                        .map(|k| k.to_string())
+                        .collect();
+                    let comment = localized_attr(section, "Comment", &locales).unwrap_or_default();
+                    let categories: Vec<String> = section
+                        .attr("Categories")
                         .first()
                         .map(|s| s.to_string())
-                        .unwrap_or_else(|| "Unknown".to_string());
+                        .unwrap_or_default()
+                        .split(';')
+                        .filter(|c| !c.is_empty())
+                        .map(|c| c.to_string())
+                        .collect();
+
+                    // 4. Handle Terminal (TUI apps like htop ship their own .desktop)
+                    let is_terminal = is_true("Terminal");
+
+                    let flatpak_id = section
+                        .attr("X-Flatpak")
+                        .first()
+                        .map(|s| s.to_string())
+                        .unwrap_or_default();
 
-                    // 4. Handle Exec
+                    let icon = section
+                        .attr("Icon")
+                        .first()
+                        .map(|s| s.to_string())
+                        .unwrap_or_default();
+
+                    // 5. Handle Exec
                     let exec_raw = section
                         .attr("Exec")
                         .first()
@@ -134,23 +339,73 @@ fn scan_apps_fresh(db: &Database) -> Vec<AppEntry> {
                             .map(|s| s.to_string_lossy().to_string())
                             .unwrap_or(binary_name);
 
-                        known_execs.insert(simple_bin);
+                        // Every flatpak Exec starts with the flatpak binary itself
+                        if flatpak_id.is_empty() {
+                            known_execs.insert(simple_bin);
+                        }
 
-                        if seen_names.insert(name.clone()) {
-                            // Use batch-loaded DB data instead of individual query
-                            let (_, base_score, usage, last_used) = db_data
-                                .get(&name)
-                                .copied()
-                                .unwrap_or((false, 0, 0, 0));
-                            
-                            let total = base_score + (usage * 10);
-                            let is_dormant =
-                                last_used > 0 && (now.saturating_sub(last_used) > thirty_days);
+                        // Same-named apps from different desktop files are both kept;
+                        // the name set only keeps $PATH duplicates out below
+                        seen_names.insert(name.clone());
+                        let (total, is_dormant) = score_for(&name);
+
+                        apps.push(AppEntry {
+                            name: name.clone(),
+                            generic_name: generic_name.clone(),
+                            keywords: keywords.clone(),
+                            comment: comment.clone(),
+                            categories: categories.clone(),
+                            exec: exec_raw,
+                            is_cli_only: false,
+                            is_action: false,
+                            is_terminal,
+                            icon: icon.clone(),
+                            desktop_path: entry.path().to_string_lossy().to_string(),
+                            flatpak_id: flatpak_id.clone(),
+                            total_score: total,
+                            is_dormant,
+                        });
+
+                        // 6. Handle Actions - each becomes its own launchable sub-entry
+                        let action_ids = section
+                            .attr("Actions")
+                            .first()
+                            .map(|s| s.to_string())
+                            .unwrap_or_default();
+
+                        for action_id in action_ids.split(';').filter(|id| !id.is_empty()) {
+                            let action = match entry_file
+                                .section(format!("Desktop Action {}", action_id))
+                            {
+                                Some(a) => a,
+                                None => continue,
+                            };
+
+                            let (action_name, action_exec) = match (
+                                localized_attr(action, "Name", &locales),
+                                action.attr("Exec").first(),
+                            ) {
+                                (Some(n), Some(e)) if !e.is_empty() => (n, e),
+                                _ => continue,
+                            };
+
+                            let full_name = format!("{} › {}", name, action_name);
+                            let (total, is_dormant) = score_for(&full_name);
 
                             apps.push(AppEntry {
-                                name,
-                                exec: exec_raw,
+                                name: full_name,
+                                generic_name: generic_name.clone(),
+                                keywords: keywords.clone(),
+                                comment: comment.clone(),
+                                categories: categories.clone(),
+                                exec: action_exec.to_string(),
                                 is_cli_only: false,
+                                is_action: true,
+                                is_terminal,
+                                icon: icon.clone(),
+                                desktop_path: entry.path().to_string_lossy().to_string(),
+                                // Actions keep their own `flatpak run --command=...` Exec
+                                flatpak_id: String::new(),
                                 total_score: total,
                                 is_dormant,
                             });
@@ -168,10 +423,7 @@ fn scan_apps_fresh(db: &Database) -> Vec<AppEntry> {
             if !dir.exists() || !dir.is_dir() {
                 continue;
             }
-            if path_str.contains("/sbin")
-                || path_str.contains("/games")
-                || path_str.contains("/lib")
-            {
+            if is_excluded_path_dir(&dir) {
                 continue;
             }
 
@@ -193,20 +445,21 @@ fn scan_apps_fresh(db: &Database) -> Vec<AppEntry> {
                                 }
 
                                 if seen_names.insert(name.clone()) {
-                                    // Use batch-loaded DB data instead of individual query
-                                    let (_, base_score, usage, last_used) = db_data
-                                        .get(&name)
-                                        .copied()
-                                        .unwrap_or((false, 0, 0, 0));
-                                    
-                                    let total = base_score + (usage * 10);
-                                    let is_dormant = last_used > 0
-                                        && (now.saturating_sub(last_used) > thirty_days);
+                                    let (total, is_dormant) = score_for(&name);
 
                                     apps.push(AppEntry {
                                         name: name.clone(),
+                                        generic_name: String::new(),
+                                        keywords: Vec::new(),
+                                        comment: String::new(),
+                                        categories: Vec::new(),
                                         exec: name,
                                         is_cli_only: true,
+                                        is_action: false,
+                                        is_terminal: false,
+                                        icon: String::new(),
+                                        desktop_path: String::new(),
+                                        flatpak_id: String::new(),
                                         total_score: total,
                                         is_dormant,
                                     });
@@ -225,21 +478,168 @@ fn scan_apps_fresh(db: &Database) -> Vec<AppEntry> {
             .then_with(|| a.name.cmp(&b.name))
     });
 
+    tracing::debug!("app scan: {} apps in {:?}", apps.len(), started.elapsed());
     apps
 }
 
+/// `applications` dirs in XDG precedence order: $XDG_DATA_HOME, then $XDG_DATA_DIRS
+pub fn application_dirs() -> Vec<PathBuf> {
+    let mut data_dirs = Vec::new();
+
+    match env::var("XDG_DATA_HOME") {
+        Ok(data_home) if !data_home.is_empty() => data_dirs.push(PathBuf::from(data_home)),
+        _ => data_dirs.extend(dirs::home_dir().map(|h| h.join(".local/share"))),
+    }
+
+    let system_dirs = env::var("XDG_DATA_DIRS")
+        .ok()
+        .filter(|v| !v.is_empty())
+        .unwrap_or_else(|| "/usr/local/share:/usr/share".to_string());
+    data_dirs.extend(
+        system_dirs
+            .split(':')
+            .filter(|d| !d.is_empty())
+            .map(PathBuf::from),
+    );
+
+    // Flatpak exports, in case the session didn't add them to XDG_DATA_DIRS
+    data_dirs.extend(dirs::home_dir().map(|h| h.join(".local/share/flatpak/exports/share")));
+    data_dirs.push(PathBuf::from("/var/lib/flatpak/exports/share"));
+
+    // Nix user profile, home-manager, and the NixOS system profile
+    if let Some(home) = dirs::home_dir() {
+        data_dirs.push(home.join(".nix-profile/share"));
+        data_dirs.push(home.join(".local/state/nix/profile/share"));
+    }
+    if let Ok(user) = env::var("USER") {
+        data_dirs.push(PathBuf::from(format!("/etc/profiles/per-user/{}/share", user)));
+    }
+    data_dirs.push(PathBuf::from("/run/current-system/sw/share"));
+
+    // Linuxbrew rarely makes it into XDG_DATA_DIRS
+    data_dirs.push(PathBuf::from("/home/linuxbrew/.linuxbrew/share"));
+
+    // Dedup on the resolved path - several Nix profiles can link to the same store dir
+    let mut seen = HashSet::new();
+    let mut search_dirs: Vec<PathBuf> = Vec::new();
+    for dir in data_dirs {
+        let apps_dir = dir.join("applications");
+        let resolved = fs::canonicalize(&apps_dir).unwrap_or_else(|_| apps_dir.clone());
+        if seen.insert(resolved) {
+            search_dirs.push(apps_dir);
+        }
+    }
+    search_dirs
+}
+
+/// Skip sbin/games/lib dirs on $PATH. Matches whole components, so Nix store
+/// entries like /nix/store/<hash>-glibc-2.39/bin are not caught by accident
+fn is_excluded_path_dir(dir: &Path) -> bool {
+    let components: Vec<String> = dir
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+
+    if matches!(components.last().map(String::as_str), Some("sbin" | "games")) {
+        return true;
+    }
+
+    // Store path names are package names, not FHS dirs
+    if dir.starts_with("/nix/store") {
+        return false;
+    }
+
+    components
+        .iter()
+        .any(|c| c == "sbin" || c == "games" || c.starts_with("lib"))
+}
+
+/// Locale keys to try for `Key[locale]` lookups, most specific first.
+/// Follows the Desktop Entry spec: lang_COUNTRY@MODIFIER, lang_COUNTRY, lang@MODIFIER, lang
+fn locale_candidates() -> Vec<String> {
+    let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+        .iter()
+        .filter_map(|var| env::var(var).ok())
+        .find(|value| !value.is_empty())
+        .unwrap_or_default();
+
+    // Strip the encoding: de_DE.UTF-8@euro -> de_DE@euro
+    let (base, modifier) = match locale.split_once('@') {
+        Some((base, modifier)) => (base, Some(modifier)),
+        None => (locale.as_str(), None),
+    };
+    let base = base.split('.').next().unwrap_or("");
+    let (lang, country) = match base.split_once('_') {
+        Some((lang, country)) => (lang, Some(country)),
+        None => (base, None),
+    };
+
+    if lang.is_empty() || lang == "C" || lang == "POSIX" {
+        return Vec::new();
+    }
+
+    let mut candidates = Vec::new();
+    if let (Some(country), Some(modifier)) = (country, modifier) {
+        candidates.push(format!("{}_{}@{}", lang, country, modifier));
+    }
+    if let Some(country) = country {
+        candidates.push(format!("{}_{}", lang, country));
+    }
+    if let Some(modifier) = modifier {
+        candidates.push(format!("{}@{}", lang, modifier));
+    }
+    candidates.push(lang.to_string());
+    candidates
+}
+
+/// Read `key`, preferring the best localized variant over the plain value
+fn localized_attr(section: &Section, key: &str, locales: &[String]) -> Option<String> {
+    locales
+        .iter()
+        .find_map(|locale| section.attr_with_param(key, locale).first())
+        .or_else(|| section.attr(key).first())
+        .filter(|value| !value.is_empty())
+        .map(|value| value.to_string())
+}
+
+/// Batch-loaded ranking data shared by the fresh scan and the cache path
+struct AppScores {
+    now: u64,
+    dormant_secs: u64,
+    prefs: HashMap<String, (bool, i32, i32, u64)>,
+    frecency: HashMap<String, i32>,
+}
+
+impl AppScores {
+    fn load(db: &Database, config: &AppsConfig) -> Self {
+        Self {
+            now: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .unwrap()
+                .as_secs(),
+            dormant_secs: config.dormant_secs(),
+            prefs: db.get_all_app_data(),
+            frecency: db.get_all_frecency(),
+        }
+    }
+
+    /// (seed score + frecency, is dormant)
+    fn get(&self, name: &str) -> (i32, bool) {
+        let (_, base_score, _, last_used) =
+            self.prefs.get(name).copied().unwrap_or((false, 0, 0, 0));
+
+        let total = base_score + self.frecency.get(name).copied().unwrap_or(0);
+        let is_dormant = last_used > 0 && (self.now.saturating_sub(last_used) > self.dormant_secs);
+        (total, is_dormant)
+    }
+}
+
 /// Enrich cached apps with fresh database data
-fn enrich_apps_with_db_data(mut apps: Vec<AppEntry>, db: &Database) -> Vec<AppEntry> {
-    let now = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs();
-    let thirty_days = 30 * 24 * 60 * 60;
+fn enrich_apps_with_db_data(mut apps: Vec<AppEntry>, db: &Database, config: &AppsConfig) -> Vec<AppEntry> {
+    let scores = AppScores::load(db, config);
 
     for app in &mut apps {
-        let (_, base_score, usage, last_used) = db.get_app_data(&app.name);
-        app.total_score = base_score + (usage * 10);
-        app.is_dormant = last_used > 0 && (now.saturating_sub(last_used) > thirty_days);
+        (app.total_score, app.is_dormant) = scores.get(&app.name);
     }
 
     apps.sort_by(|a, b| {
@@ -258,15 +658,33 @@ fn enrich_apps_with_db_data(mut apps: Vec<AppEntry>, db: &Database) -> Vec<AppEn
 #[derive(serde::Serialize, serde::Deserialize)]
 struct CachedApp {
     name: String,
+    #[serde(default)]
+    generic_name: String,
+    #[serde(default)]
+    keywords: Vec<String>,
+    #[serde(default)]
+    comment: String,
+    #[serde(default)]
+    categories: Vec<String>,
     exec: String,
     is_cli_only: bool,
+    #[serde(default)]
+    is_action: bool,
+    #[serde(default)]
+    is_terminal: bool,
+    #[serde(default)]
+    icon: String,
+    #[serde(default)]
+    desktop_path: String,
+    #[serde(default)]
+    flatpak_id: String,
 }
 
-fn get_cache_path() -> PathBuf {
+pub fn get_cache_path(file_name: &str) -> PathBuf {
     let mut path = dirs::cache_dir().unwrap_or_else(|| PathBuf::from("."));
     path.push("rula");
     std::fs::create_dir_all(&path).ok();
-    path.push("apps.json");
+    path.push(file_name);
     path
 }
 
@@ -275,26 +693,44 @@ fn save_app_cache(apps: &[AppEntry]) -> io::Result<()> {
         .iter()
         .map(|a| CachedApp {
             name: a.name.clone(),
+            generic_name: a.generic_name.clone(),
+            keywords: a.keywords.clone(),
+            comment: a.comment.clone(),
+            categories: a.categories.clone(),
             exec: a.exec.clone(),
             is_cli_only: a.is_cli_only,
+            is_action: a.is_action,
+            is_terminal: a.is_terminal,
+            icon: a.icon.clone(),
+            desktop_path: a.desktop_path.clone(),
+            flatpak_id: a.flatpak_id.clone(),
         })
         .collect();
 
     let json = serde_json::to_string(&cached)?;
-    fs::write(get_cache_path(), json)?;
+    fs::write(get_cache_path("apps.json"), json)?;
     Ok(())
 }
 
 fn load_app_cache() -> io::Result<Vec<AppEntry>> {
-    let json = fs::read_to_string(get_cache_path())?;
+    let json = fs::read_to_string(get_cache_path("apps.json"))?;
     let cached: Vec<CachedApp> = serde_json::from_str(&json)?;
 
     let apps = cached
         .into_iter()
         .map(|c| AppEntry {
             name: c.name,
+            generic_name: c.generic_name,
+            keywords: c.keywords,
+            comment: c.comment,
+            categories: c.categories,
             exec: c.exec,
             is_cli_only: c.is_cli_only,
+            is_action: c.is_action,
+            is_terminal: c.is_terminal,
+            icon: c.icon,
+            desktop_path: c.desktop_path,
+            flatpak_id: c.flatpak_id,
             total_score: 0,
             is_dormant: false,
         })
@@ -303,126 +739,1435 @@ fn load_app_cache() -> io::Result<Vec<AppEntry>> {
     Ok(apps)
 }
 
+// ============================================================================
+// CONFIG-DEFINED ENTRIES
+// ============================================================================
+
+/// Scanned apps plus the user's aliases and `[[entries]]`, scored and ranked together
+pub fn with_config_entries(mut apps: Vec<AppEntry>, config: &Config, db: &Database) -> Vec<AppEntry> {
+    let scanned = apps.len();
+
+    // `yt = "firefox https://youtube.com"` becomes an entry named "yt"
+    apps.extend(
+        config
+            .aliases
+            .iter()
+            .map(|(keyword, command)| config_entry(keyword, command, false, "")),
+    );
+    apps.extend(
+        config
+            .entries
+            .iter()
+            .filter(|e| !e.name.is_empty() && !e.exec.is_empty())
+            .map(|e| config_entry(&e.name, &e.exec, e.terminal, &e.icon)),
+    );
+
+    if apps.len() == scanned {
+        return apps;
+    }
+    enrich_apps_with_db_data(apps, db, &config.apps)
+}
+
+/// The command doubles as the comment so it is searchable too
+fn config_entry(name: &str, command: &str, terminal: bool, icon: &str) -> AppEntry {
+    AppEntry {
+        name: name.to_string(),
+        generic_name: String::new(),
+        keywords: Vec::new(),
+        comment: command.to_string(),
+        categories: Vec::new(),
+        // Commands are plain shell words, so a literal '%' (e.g. in URLs)
+        // must not be read as a field code
+        exec: command.replace('%', "%%"),
+        is_cli_only: false,
+        is_action: false,
+        is_terminal: terminal,
+        icon: icon.to_string(),
+        desktop_path: String::new(),
+        flatpak_id: String::new(),
+        total_score: 0,
+        is_dormant: false,
+    }
+}
+
+// ============================================================================
+// PROJECT SCANNING WITH CACHE
+// ============================================================================
+
+/// Load git projects from cache or rescan the configured roots
+pub fn scan_projects(config: &ProjectsConfig) -> Vec<String> {
+    if let Ok(cached) = load_project_cache() {
+        if !cached.is_empty() {
+            return cached;
+        }
+    }
+
+    let projects = scan_projects_fresh(config);
+    if let Err(e) = save_project_cache(&projects) {
+        tracing::warn!("saving project cache failed: {}", e);
+    }
+    projects
+}
+
+/// Force rebuild the project cache
+pub fn rebuild_project_cache(config: &ProjectsConfig) -> io::Result<()> {
+    let projects = scan_projects_fresh(config);
+    save_project_cache(&projects)
+}
+
+fn scan_projects_fresh(config: &ProjectsConfig) -> Vec<String> {
+    let mut projects = Vec::new();
+
+    for root in &config.roots {
+        let root = expand_tilde(root);
+        if !root.is_dir() {
+            continue;
+        }
+
+        let mut walker = WalkDir::new(&root)
+            .max_depth(config.max_depth)
+            .into_iter();
+
+        while let Some(entry) = walker.next() {
+            let entry = match entry {
+                Ok(e) => e,
+                Err(_) => continue,
+            };
+            if !entry.file_type().is_dir() {
+                continue;
+            }
+
+            // Hidden directories never hold projects worth listing
+            let is_hidden = entry.file_name().to_string_lossy().starts_with('.');
+            if is_hidden && entry.depth() > 0 {
+                walker.skip_current_dir();
+                continue;
+            }
+
+            // A repository is a leaf - nested checkouts are not listed separately
+            if entry.path().join(".git").exists() {
+                projects.push(entry.path().to_string_lossy().to_string());
+                walker.skip_current_dir();
+            }
+        }
+    }
+
+    projects.sort();
+    projects.dedup();
+    projects
+}
+
+fn save_project_cache(projects: &[String]) -> io::Result<()> {
+    let json = serde_json::to_string(projects)?;
+    fs::write(get_cache_path("projects.json"), json)?;
+    Ok(())
+}
+
+fn load_project_cache() -> io::Result<Vec<String>> {
+    let json = fs::read_to_string(get_cache_path("projects.json"))?;
+    Ok(serde_json::from_str(&json)?)
+}
+
 // ============================================================================
 // FILE STREAMING SEARCH (fd-like performance)
 // ============================================================================
 
+/// One matching line from a content search
+#[derive(Clone, Debug)]
+pub struct GrepMatch {
+    pub path: String,
+    /// 1-based, as editors expect
+    pub line: usize,
+    pub text: String,
+}
+
+/// A cancellable worker thread streaming snapshots of its results
+pub struct SearchJob<T> {
+    cancel: Arc<AtomicBool>,
+    results: Option<Receiver<Vec<T>>>,
+}
+
+impl<T: Send + 'static> SearchJob<T> {
+    pub fn new() -> Self {
+        Self {
+            cancel: Arc::new(AtomicBool::new(false)),
+            results: None,
+        }
+    }
+
+    /// Cancel the running search and hand `work` a fresh token and channel
+    pub fn start<F>(&mut self, work: F)
+    where
+        F: FnOnce(&AtomicBool, &Sender<Vec<T>>) + Send + 'static,
+    {
+        self.cancel();
+
+        let cancel = Arc::new(AtomicBool::new(false));
+        let (tx, rx) = mpsc::channel();
+        let token = Arc::clone(&cancel);
+
+        // Dropping `tx` when the worker returns marks the search as finished
+        thread::spawn(move || work(&token, &tx));
+
+        self.cancel = cancel;
+        self.results = Some(rx);
+    }
+
+    /// Stop the running search; its results are never delivered
+    pub fn cancel(&mut self) {
+        self.cancel.store(true, Ordering::Relaxed);
+        self.results = None;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.results.is_some()
+    }
+
+    /// Newest partial (or final) results, if any arrived
+    pub fn poll(&mut self) -> Option<Vec<T>> {
+        let rx = self.results.as_ref()?;
+        let mut latest = None;
+        loop {
+            match rx.try_recv() {
+                // Each update is a full snapshot, so only the newest matters
+                Ok(results) => latest = Some(results),
+                Err(TryRecvError::Empty) => break,
+                Err(TryRecvError::Disconnected) => {
+                    self.results = None;
+                    break;
+                }
+            }
+        }
+        latest
+    }
+}
+
 pub struct FileSearcher {
-    home: PathBuf,
+    scope: SearchScope,
+    // Flattened persistent index, loaded on the first search and shared with workers
+    index: Option<Arc<Vec<String>>>,
+    index_loaded: bool,
+    // Background refresh of the on-disk index
+    index_refresh: Option<Receiver<Vec<String>>>,
+    // Index paths that matched the last finished file-name search
+    index_matches: Arc<Mutex<MatchCache>>,
+    // In-flight searches by file name and by content
+    files: SearchJob<String>,
+    grep: SearchJob<GrepMatch>,
 }
 
 impl FileSearcher {
-    pub fn new() -> Self {
+    pub fn new(scope: SearchScope) -> Self {
         Self {
-            home: dirs::home_dir().unwrap_or_else(|| PathBuf::from("/")),
+            scope,
+            index: None,
+            index_loaded: false,
+            index_refresh: None,
+            index_matches: Arc::default(),
+            files: SearchJob::new(),
+            grep: SearchJob::new(),
         }
     }
 
-    /// Stream file search - returns results as they're found (lazy)
-    /// OPTIMIZED: Uses rayon for parallel fuzzy matching
-    pub fn search(&self, query: &str, limit: usize) -> Vec<String> {
-        use rayon::prelude::*;
-        
+    /// Load the saved index and kick off a background refresh if it is missing or stale.
+    /// Until an index exists, searches fall back to walking the tree live.
+    fn ensure_index(&mut self) {
+        if !self.index_loaded {
+            self.index_loaded = true;
+
+            let needs_refresh = match FileIndex::load() {
+                Ok(index) => {
+                    let stale = index.is_stale();
+                    self.index = Some(Arc::new(index.paths()));
+                    stale
+                }
+                Err(_) => true,
+            };
+
+            if needs_refresh {
+                let (tx, rx) = mpsc::channel();
+                let scope = self.scope.clone();
+                thread::spawn(move || {
+                    let started = Instant::now();
+                    match update_file_index(&scope) {
+                        Ok(index) => {
+                            tracing::debug!("file index: {} files in {:?}", index.paths().len(), started.elapsed());
+                            let _ = tx.send(index.paths());
+                        }
+                        Err(e) => tracing::warn!("updating file index failed: {}", e),
+                    }
+                });
+                self.index_refresh = Some(rx);
+            }
+        }
+
+        if let Some(rx) = &self.index_refresh {
+            match rx.try_recv() {
+                Ok(paths) => {
+                    self.index = Some(Arc::new(paths));
+                    self.index_refresh = None;
+                    // Not cleared in place: searches still running on the old index would refill it
+                    self.index_matches = Arc::default();
+                }
+                Err(TryRecvError::Disconnected) => self.index_refresh = None,
+                Err(TryRecvError::Empty) => {}
+            }
+        }
+    }
+
+    /// Start searching file names on a worker thread, cancelling any search still
+    /// running. Results stream in through `poll_results`.
+    pub fn start_search(&mut self, query: &str, limit: usize) {
+        self.cancel();
+        if query.is_empty() {
+            return;
+        }
+
+        self.ensure_index();
+        let scope = self.scope.clone();
+        let index = self.index.clone();
+        let matches = self.index_matches.clone();
+        let query = query.to_string();
+        self.files.start(move |cancel, tx| {
+            search_files(&scope, index.as_deref(), &matches, &query, limit, cancel, tx)
+        });
+    }
+
+    /// Start searching file contents on a worker thread, cancelling any search
+    /// still running. Results stream in through `poll_grep_results`.
+    pub fn start_grep(&mut self, query: &str, limit: usize) {
+        self.cancel();
         if query.is_empty() {
-            return Vec::new();
+            return;
         }
 
-        let query_lower = query.to_lowercase();
+        self.ensure_index();
+        let scope = self.scope.clone();
+        let index = self.index.clone();
+        let query = query.to_string();
+        self.grep.start(move |cancel, tx| {
+            grep_files(&scope, index.as_deref(), &query, limit, cancel, tx)
+        });
+    }
+
+    /// Stop any in-flight search; its results are never delivered
+    pub fn cancel(&mut self) {
+        self.files.cancel();
+        self.grep.cancel();
+    }
+
+    pub fn is_searching(&self) -> bool {
+        self.files.is_running() || self.grep.is_running()
+    }
+
+    /// Newest partial (or final) file-name results, if any arrived
+    pub fn poll_results(&mut self) -> Option<Vec<String>> {
+        self.files.poll()
+    }
 
-        // Step 1: Collect candidate paths (with pre-filter)
-        let mut candidates = Vec::new();
-        let walker = ignore::WalkBuilder::new(&self.home)
-            .hidden(false)
-            .max_depth(Some(5))
-            .git_ignore(true)
-            .ignore(true)
-            .build();
+    /// Newest partial (or final) content matches, if any arrived
+    pub fn poll_grep_results(&mut self) -> Option<Vec<GrepMatch>> {
+        self.grep.poll()
+    }
+}
 
-        for entry in walker {
-            // Collect more candidates for better fuzzy matching
-            if candidates.len() >= limit * 10 {
-                break;
+/// Index paths matched per batch before the best results so far are published
+const INDEX_BATCH: usize = 16_384;
+/// Live-walk candidates gathered before publishing, unless `WALK_FLUSH` passes first
+const WALK_BATCH: usize = 256;
+const WALK_FLUSH: Duration = Duration::from_millis(20);
+
+/// Search the persistent index, or stream a live walk until one is built.
+/// The best `limit` matches so far are sent after every batch, so the first
+/// results show up long before the search finishes. Stops as soon as `cancel` is set.
+/// A finished index search leaves its matches in `index_matches`, so a longer
+/// query only re-scores those.
+fn search_files(
+    scope: &SearchScope,
+    index: Option<&Vec<String>>,
+    index_matches: &Mutex<MatchCache>,
+    query: &str,
+    limit: usize,
+    cancel: &AtomicBool,
+    tx: &Sender<Vec<String>>,
+) {
+    let started = Instant::now();
+    let matcher = QueryMatcher::parse(query);
+    let mut best: Vec<(i64, String)> = Vec::new();
+
+    match index {
+        Some(paths) => {
+            let survivors = lock(index_matches).survivors(query, &[], paths.len()).map(<[usize]>::to_vec);
+            let pool = survivors.unwrap_or_else(|| (0..paths.len()).collect());
+            let mut matched = Vec::new();
+
+            for batch in pool.chunks(INDEX_BATCH) {
+                if cancel.load(Ordering::Relaxed) {
+                    return;
+                }
+                let found = match_indexed(&matcher, paths, batch);
+                matched.extend(found.iter().map(|&(_, i)| i));
+                let found = found.into_iter().map(|(score, i)| (score, paths[i].clone())).collect();
+                if merge_top(&mut best, found, limit) && tx.send(top_paths(&best)).is_err() {
+                    return;
+                }
             }
 
-            if let Ok(entry) = entry {
-                if entry.file_type().map_or(false, |ft| ft.is_file()) {
-                    let path_str = entry.path().to_string_lossy().to_string();
+            lock(index_matches).remember(query, &[], paths.len(), matched);
+        }
+        None => {
+            // Collect more candidates than needed for better fuzzy matching
+            let max = limit * 10;
+            let mut seen = 0;
+            let mut batch = Vec::with_capacity(WALK_BATCH);
+            let mut last_flush = Instant::now();
+
+            for path_str in scope.walk_files() {
+                if seen >= max || cancel.load(Ordering::Relaxed) {
+                    break;
+                }
+
+                // Quick pre-filter: skip paths that can't possibly match
+                if matcher.could_match(&path_str) {
+                    batch.push(path_str);
+                    seen += 1;
+                }
 
-                    // Quick pre-filter: skip if doesn't contain query chars
-                    let path_lower = path_str.to_lowercase();
-                    if query_lower.chars().all(|c| path_lower.contains(c)) {
-                        candidates.push(path_str);
+                if batch.len() >= WALK_BATCH || (!batch.is_empty() && last_flush.elapsed() >= WALK_FLUSH) {
+                    let found = match_batch(&matcher, &batch);
+                    batch.clear();
+                    last_flush = Instant::now();
+                    if merge_top(&mut best, found, limit) && tx.send(top_paths(&best)).is_err() {
+                        return;
                     }
                 }
             }
-        }
 
-        // Step 2: PARALLEL fuzzy matching with rayon
-        let matcher = SkimMatcherV2::default();
-        let mut results: Vec<(i64, String)> = candidates
-            .par_iter()  // <-- RAYON: Parallel iterator
-            .filter_map(|path| {
-                matcher.fuzzy_match(path, query).map(|score| (score, path.clone()))
-            })
-            .collect();
+            let found = match_batch(&matcher, &batch);
+            merge_top(&mut best, found, limit);
+        }
+    }
 
-        // Step 3: Sort and return top N
-        results.sort_by(|a, b| b.0.cmp(&a.0));
-        results.truncate(limit);
-        results.into_iter().map(|(_, path)| path).collect()
+    // Always publish the final list, even when nothing matched
+    if !cancel.load(Ordering::Relaxed) {
+        tracing::debug!(query, indexed = index.is_some(), "file search: {} matches in {:?}", best.len(), started.elapsed());
+        let _ = tx.send(top_paths(&best));
     }
 }
 
-// ============================================================================
-// FUZZY SEARCH FOR APPS
-// ============================================================================
+/// Pre-filter cheaply, then score the survivors in parallel
+/// OPTIMIZED: Uses rayon for parallel fuzzy matching
+fn match_batch(matcher: &QueryMatcher, paths: &[String]) -> Vec<(i64, String)> {
+    use rayon::prelude::*;
 
-pub fn fuzzy_search_apps<'a>(query: &str, apps: &'a [AppEntry]) -> Vec<&'a AppEntry> {
+    paths
+        .par_iter()  // <-- RAYON: Parallel iterator
+        .filter(|path| matcher.could_match(path))
+        .filter_map(|path| matcher.score(path).map(|score| (score, path.clone())))
+        .collect()
+}
+
+/// Score the index paths at `batch` in parallel, keeping their positions
+fn match_indexed(matcher: &QueryMatcher, paths: &[String], batch: &[usize]) -> Vec<(i64, usize)> {
     use rayon::prelude::*;
-    
-    let matcher = SkimMatcherV2::default();
-    
-    // RAYON: Parallel fuzzy matching for apps
-    let mut matches: Vec<_> = apps
-        .par_iter()  // <-- PARALLEL
-        .filter_map(|app| matcher.fuzzy_match(&app.name, query).map(|s| (s, app)))
-        .collect();
 
-    matches.sort_by(|a, b| {
-        b.0.cmp(&a.0)
-            .then(b.1.total_score.cmp(&a.1.total_score))
-    });
+    batch
+        .par_iter()
+        .filter_map(|&i| matcher.score(&paths[i]).map(|score| (score, i)))
+        .collect()
+}
 
-    matches.into_iter().take(50).map(|(_, i)| i).collect()
+/// A poisoned lock only means a search thread panicked; the cache is still usable
+fn lock(cache: &Mutex<MatchCache>) -> std::sync::MutexGuard<'_, MatchCache> {
+    cache.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Fold new matches into the running top N. Returns true if anything was added.
+fn merge_top(best: &mut Vec<(i64, String)>, found: Vec<(i64, String)>, limit: usize) -> bool {
+    if found.is_empty() {
+        return false;
+    }
+    best.extend(found);
+    best.sort_by_key(|r| std::cmp::Reverse(r.0));
+    best.truncate(limit);
+    true
+}
+
+fn top_paths(best: &[(i64, String)]) -> Vec<String> {
+    best.iter().map(|(_, path)| path.clone()).collect()
 }
 
 // ============================================================================
-// DATABASE SEEDING
+// CONTENT SEARCH (grep)
 // ============================================================================
 
-pub fn seed_database(db: &Database) {
-    println!("Seeding database from Pacman... this might take a few seconds.");
+/// Files grepped in parallel before the matches so far are published
+const GREP_BATCH: usize = 64;
+/// Larger files are almost never worth grepping from a launcher
+const GREP_MAX_FILE_BYTES: u64 = 1024 * 1024;
+/// Snippets are trimmed to this many characters
+const GREP_SNIPPET_CHARS: usize = 200;
+
+/// Grep the contents of indexed files (or a live walk until an index exists).
+/// Smart case: the match is case-sensitive only when the query has uppercase.
+/// Matches are published in batches until `limit` is reached or `cancel` is set.
+fn grep_files(
+    scope: &SearchScope,
+    index: Option<&Vec<String>>,
+    query: &str,
+    limit: usize,
+    cancel: &AtomicBool,
+    tx: &Sender<Vec<GrepMatch>>,
+) {
+    use rayon::prelude::*;
 
-    let output = Command::new("sh")
-        .arg("-c")
-        .arg("pacman -Qqe | xargs pacman -Ql | grep '/usr/bin/'")
-        .output()
-        .expect("Failed to execute pacman");
+    let started = Instant::now();
+    let case_sensitive = query.chars().any(char::is_uppercase);
+    let needle = if case_sensitive { query.to_string() } else { query.to_lowercase() };
 
-    let stdout = String::from_utf8_lossy(&output.stdout);
+    let paths: Box<dyn Iterator<Item = String>> = match index {
+        Some(paths) => Box::new(paths.iter().cloned()),
+        None => Box::new(scope.walk_files()),
+    };
 
-    let mut count = 0;
-    for line in stdout.lines() {
-        let parts: Vec<&str> = line.split_whitespace().collect();
-        if parts.len() >= 2 {
-            let path = Path::new(parts[1]);
-            if let Some(name_os) = path.file_name() {
-                let name = name_os.to_string_lossy().to_string();
-                let _ = db.set_base_score(&name, 50);
-                count += 1;
+    let mut matches: Vec<GrepMatch> = Vec::new();
+    let mut batch = Vec::with_capacity(GREP_BATCH);
+    let mut paths = paths.peekable();
+
+    while paths.peek().is_some() && matches.len() < limit {
+        if cancel.load(Ordering::Relaxed) {
+            return;
+        }
+
+        batch.clear();
+        batch.extend(paths.by_ref().take(GREP_BATCH));
+
+        // Collecting keeps batch order, so results stay in walk order
+        let found: Vec<GrepMatch> = batch
+            .par_iter()
+            .flat_map_iter(|path| grep_file(path, &needle, case_sensitive, limit, cancel))
+            .collect();
+
+        if !found.is_empty() {
+            matches.extend(found);
+            matches.truncate(limit);
+            if tx.send(matches.clone()).is_err() {
+                return;
             }
         }
     }
 
-    println!("Seeded {} apps with +50 score.", count);
+    // Always publish the final list, even when nothing matched
+    if !cancel.load(Ordering::Relaxed) {
+        tracing::debug!(query, indexed = index.is_some(), "grep: {} matches in {:?}", matches.len(), started.elapsed());
+        let _ = tx.send(matches);
+    }
+}
+
+/// Matching lines of one text file; binary and oversized files are skipped
+fn grep_file(path: &str, needle: &str, case_sensitive: bool, limit: usize, cancel: &AtomicBool) -> Vec<GrepMatch> {
+    if cancel.load(Ordering::Relaxed) {
+        return Vec::new();
+    }
+
+    let too_big = fs::metadata(path).map(|m| m.len() > GREP_MAX_FILE_BYTES).unwrap_or(true);
+    if too_big {
+        return Vec::new();
+    }
+    let bytes = match fs::read(path) {
+        Ok(bytes) => bytes,
+        Err(_) => return Vec::new(),
+    };
+    if looks_binary(&bytes) {
+        return Vec::new();
+    }
+
+    let text = String::from_utf8_lossy(&bytes);
+    text.lines()
+        .enumerate()
+        .filter(|(_, line)| {
+            if case_sensitive {
+                line.contains(needle)
+            } else {
+                line.to_lowercase().contains(needle)
+            }
+        })
+        .take(limit)
+        .map(|(i, line)| GrepMatch {
+            path: path.to_string(),
+            line: i + 1,
+            text: line.trim().chars().take(GREP_SNIPPET_CHARS).collect(),
+        })
+        .collect()
+}
+
+/// Bytes at the start of a file checked by `looks_binary`
+const BINARY_SNIFF_BYTES: usize = 8192;
+
+/// Same heuristic as grep: a NUL byte near the start means binary
+fn looks_binary(head: &[u8]) -> bool {
+    head.iter().take(BINARY_SNIFF_BYTES).any(|&b| b == 0)
+}
+
+// ============================================================================
+// QUERY MATCHING
+// ============================================================================
+
+/// Score given to every exact/regex hit, minus the offset of the match
+const LITERAL_MATCH_SCORE: i64 = 1000;
+
+thread_local! {
+    // Matcher scratch space is large, so each (rayon) thread keeps its own
+    static MATCHER: RefCell<(Matcher, Vec<char>)> = RefCell::new((Matcher::default(), Vec::new()));
+}
+
+/// How a query is matched. Fuzzy by default, with each space-separated word
+/// matched on its own; `'text` asks for an exact substring and `/pattern/` for
+/// a regular expression. All are smart case: they ignore case unless the query
+/// contains an uppercase letter.
+pub enum QueryMatcher {
+    Fuzzy { pattern: Pattern, query_folded: Vec<char> },
+    Exact { needle: String, case_sensitive: bool },
+    Regex(Regex),
+}
+
+impl QueryMatcher {
+    pub fn parse(query: &str) -> Self {
+        let case_sensitive = query.chars().any(char::is_uppercase);
+
+        if let Some(needle) = query.strip_prefix('\'') {
+            return Self::exact(needle, case_sensitive);
+        }
+
+        if let Some(pattern) = query.strip_prefix('/') {
+            // The closing slash is optional so results update while typing
+            let pattern = pattern.strip_suffix('/').unwrap_or(pattern);
+            // Half-typed patterns like "/foo(" fall back to a literal match
+            return match RegexBuilder::new(pattern).case_insensitive(!case_sensitive).build() {
+                Ok(regex) => Self::Regex(regex),
+                Err(_) => Self::exact(pattern, case_sensitive),
+            };
+        }
+
+        Self::Fuzzy {
+            pattern: Pattern::new(query, CaseMatching::Smart, Normalization::Smart, AtomKind::Fuzzy),
+            query_folded: query.chars().filter(|c| !c.is_whitespace()).map(fold_char).collect(),
+        }
+    }
+
+    fn exact(needle: &str, case_sensitive: bool) -> Self {
+        let needle = if case_sensitive { needle.to_string() } else { needle.to_lowercase() };
+        Self::Exact { needle, case_sensitive }
+    }
+
+    /// Whether everything `query` matches was also matched by this shorter one.
+    /// Typing more only narrows fuzzy and exact queries; a regex can widen ("/a" -> "/a|b").
+    fn narrows_to(previous: &str, query: &str) -> bool {
+        query.starts_with(previous) && (previous.is_empty() || !previous.starts_with('/'))
+    }
+
+    /// Higher is better; None if `text` doesn't match.
+    /// Literal matches rank earlier occurrences first.
+    pub fn score(&self, text: &str) -> Option<i64> {
+        let offset = match self {
+            Self::Fuzzy { pattern, .. } => {
+                return MATCHER.with_borrow_mut(|(matcher, buf)| {
+                    pattern.score(Utf32Str::new(text, buf), matcher).map(i64::from)
+                });
+            }
+            Self::Exact { needle, case_sensitive: true } => text.find(needle.as_str())?,
+            Self::Exact { needle, case_sensitive: false } => find_ignore_case(text, needle)?.0,
+            Self::Regex(regex) => regex.find(text)?.start(),
+        };
+        Some(LITERAL_MATCH_SCORE - (offset as i64).min(LITERAL_MATCH_SCORE - 1))
+    }
+
+    /// Char indices of `text` that matched, for highlighting
+    pub fn indices(&self, text: &str) -> Option<Vec<usize>> {
+        let (haystack, range) = match self {
+            Self::Fuzzy { pattern, .. } => {
+                return MATCHER.with_borrow_mut(|(matcher, buf)| {
+                    let mut indices = Vec::new();
+                    pattern.indices(Utf32Str::new(text, buf), matcher, &mut indices)?;
+                    // Each word appends its own indices
+                    indices.sort_unstable();
+                    indices.dedup();
+                    Some(indices.into_iter().map(|i| i as usize).collect())
+                });
+            }
+            Self::Exact { needle, case_sensitive: true } => {
+                (text.to_string(), text.find(needle.as_str()).map(|start| start..start + needle.len())?)
+            }
+            Self::Exact { needle, case_sensitive: false } => {
+                let (first, len) = find_ignore_case(text, needle)?;
+                return Some((first..first + len).collect());
+            }
+            Self::Regex(regex) => (text.to_string(), regex.find(text)?.range()),
+        };
+
+        // Byte range -> char indices
+        let first = haystack[..range.start].chars().count();
+        let len = haystack[range].chars().count();
+        Some((first..first + len).collect())
+    }
+
+    /// Cheap necessary condition for `score`, used to thin out large candidate sets
+    pub fn could_match(&self, text: &str) -> bool {
+        match self {
+            Self::Fuzzy { query_folded, .. } => {
+                let text_folded: Vec<char> = text.chars().map(fold_char).collect();
+                query_folded.iter().all(|c| text_folded.contains(c))
+            }
+            _ => self.score(text).is_some(),
+        }
+    }
+}
+
+/// Strip diacritics, then case fold, the way nucleo compares chars ("É" -> "e").
+/// Folding both sides keeps `could_match` from rejecting what the pattern would match.
+fn fold_char(c: char) -> char {
+    nucleo_matcher::chars::to_lower_case(nucleo_matcher::chars::normalize(c))
+}
+
+/// First match of the lowercase `needle` in `text`, as (first char, char count).
+/// Walks the original chars so the span stays on `text` even where lowercasing
+/// changes a char's length ("İ" -> "i̇").
+fn find_ignore_case(text: &str, needle: &str) -> Option<(usize, usize)> {
+    if needle.is_empty() {
+        return Some((0, 0));
+    }
+
+    let chars: Vec<char> = text.chars().collect();
+    (0..chars.len()).find_map(|first| {
+        let mut wanted = needle.chars().peekable();
+        for (len, c) in chars[first..].iter().enumerate() {
+            for lower in c.to_lowercase() {
+                wanted.next_if_eq(&lower)?;
+                if wanted.peek().is_none() {
+                    return Some((first, len + 1));
+                }
+            }
+        }
+        None
+    })
+}
+
+/// Candidates that matched the last query. While the query only grows, each
+/// keystroke re-scores these survivors instead of every candidate.
+#[derive(Default)]
+pub struct MatchCache {
+    query: String,
+    // Anything besides the query that narrowed the candidates, e.g. "@dev" filters
+    scope: Vec<String>,
+    candidates: usize,
+    matched: Option<Vec<usize>>,
+}
+
+impl MatchCache {
+    /// Forget the survivors; call whenever the candidate list changes
+    pub fn clear(&mut self) {
+        self.matched = None;
+    }
+
+    /// Indices worth scoring for `query`, if the last query already ruled out the rest
+    fn survivors(&self, query: &str, scope: &[String], candidates: usize) -> Option<&[usize]> {
+        let matched = self.matched.as_deref()?;
+        let same_list = self.candidates == candidates && self.scope == scope;
+        (same_list && QueryMatcher::narrows_to(&self.query, query)).then_some(matched)
+    }
+
+    fn remember(&mut self, query: &str, scope: &[String], candidates: usize, matched: Vec<usize>) {
+        self.query = query.to_string();
+        self.scope = scope.to_vec();
+        self.candidates = candidates;
+        self.matched = Some(matched);
+    }
+
+    /// Score `candidates` against `query` in parallel, starting from the last
+    /// survivors when possible. Returns (score, index) pairs in candidate order.
+    pub fn matches<T: Sync>(
+        &mut self,
+        query: &str,
+        scope: &[String],
+        candidates: &[T],
+        score: impl Fn(&QueryMatcher, &T) -> Option<i64> + Sync,
+    ) -> Vec<(i64, usize)> {
+        use rayon::prelude::*;
+
+        let matcher = QueryMatcher::parse(query);
+        let score_at = |&i: &usize| score(&matcher, &candidates[i]).map(|s| (s, i));
+        let found: Vec<(i64, usize)> = match self.survivors(query, scope, candidates.len()) {
+            Some(pool) => pool.par_iter().filter_map(score_at).collect(),
+            None => (0..candidates.len()).into_par_iter().filter_map(|i| score_at(&i)).collect(),
+        };
+
+        self.remember(query, scope, candidates.len(), found.iter().map(|&(_, i)| i).collect());
+        found
+    }
+}
+
+// ============================================================================
+// FUZZY SEARCH FOR APPS
+// ============================================================================
+
+pub fn fuzzy_search_apps<'a>(query: &str, apps: &'a [AppEntry]) -> Vec<&'a AppEntry> {
+    search_apps(&mut MatchCache::default(), query, &[], apps)
+}
+
+/// Top 50 apps for `query` within the `categories` filters, best first.
+/// `cache` carries the survivors from one keystroke to the next.
+pub fn search_apps<'a>(
+    cache: &mut MatchCache,
+    query: &str,
+    categories: &[String],
+    apps: &'a [AppEntry],
+) -> Vec<&'a AppEntry> {
+    let mut matches: Vec<_> = cache
+        .matches(query, categories, apps, |matcher, app| {
+            if categories.iter().all(|c| matches_category(app, c)) {
+                app_match_score(matcher, app)
+            } else {
+                None
+            }
+        })
+        .into_iter()
+        .map(|(score, i)| (score, &apps[i]))
+        .collect();
+
+    matches.sort_by(|a, b| {
+        b.0.cmp(&a.0)
+            .then(b.1.total_score.cmp(&a.1.total_score))
+    });
+
+    matches.into_iter().take(50).map(|(_, i)| i).collect()
+}
+
+/// Name hits count fully; descriptive fields only count at a fraction so they can
+/// surface an app ("browser" finds Firefox) without outranking a name match
+fn app_match_score(matcher: &QueryMatcher, app: &AppEntry) -> Option<i64> {
+    let name = matcher.score(&app.name);
+    let generic_name = matcher.score(&app.generic_name).map(|s| s / 2);
+    let keywords = app
+        .keywords
+        .iter()
+        .filter_map(|k| matcher.score(k))
+        .max()
+        .map(|s| s / 2);
+    let comment = matcher.score(&app.comment).map(|s| s / 3);
+
+    [name, generic_name, keywords, comment].into_iter().flatten().max()
+}
+
+// ============================================================================
+// LAUNCH ARGUMENTS
+// ============================================================================
+
+/// Split "mpv -- ~/video.mkv" into ("mpv", ["/home/me/video.mkv"]).
+/// Arguments are shell-words split, with a leading `~` expanded like a shell would.
+pub fn split_launch_args(input: &str) -> (String, Vec<String>) {
+    // Only a standalone "--" word separates, so "--verbose" stays part of the query
+    let separator = input.match_indices("--").find(|(i, _)| {
+        let after = &input[i + 2..];
+        (*i == 0 || input[..*i].ends_with(' ')) && (after.is_empty() || after.starts_with(' '))
+    });
+
+    let (query, rest) = match separator {
+        Some((i, _)) => (input[..i].trim_end(), &input[i + 2..]),
+        None => return (input.to_string(), Vec::new()),
+    };
+
+    // An unclosed quote is common mid-typing - fall back to plain whitespace
+    let words = shell_words::split(rest)
+        .unwrap_or_else(|_| rest.split_whitespace().map(String::from).collect());
+
+    let args = words
+        .into_iter()
+        .map(|word| {
+            if word == "~" || word.starts_with("~/") {
+                expand_tilde(&word).to_string_lossy().to_string()
+            } else {
+                word
+            }
+        })
+        .collect();
+
+    (query.to_string(), args)
+}
+
+// ============================================================================
+// CATEGORY FILTERS
+// ============================================================================
+
+/// Short `@filter` names and the desktop categories they select
+const CATEGORY_ALIASES: &[(&str, &[&str])] = &[
+    ("dev", &["Development", "IDE"]),
+    ("games", &["Game"]),
+    ("net", &["Network", "WebBrowser"]),
+    ("web", &["WebBrowser"]),
+    ("chat", &["Chat", "InstantMessaging"]),
+    ("mail", &["Email"]),
+    ("music", &["Audio", "Music"]),
+    ("video", &["Video", "AudioVideo"]),
+    ("gfx", &["Graphics"]),
+    ("sys", &["System", "Monitor"]),
+    ("term", &["TerminalEmulator"]),
+    ("util", &["Utility"]),
+    ("edu", &["Education", "Science"]),
+];
+
+/// Split "@dev @net fire fox" into ("fire fox", ["dev", "net"])
+pub fn split_category_filters(input: &str) -> (String, Vec<String>) {
+    let mut words = Vec::new();
+    let mut categories = Vec::new();
+
+    for word in input.split(' ') {
+        match word.strip_prefix('@') {
+            Some(category) if !category.is_empty() => categories.push(category.to_lowercase()),
+            _ => words.push(word),
+        }
+    }
+
+    (words.join(" ").trim().to_string(), categories)
+}
+
+/// Alias hit, or else a case-insensitive prefix of any category ("@off" -> Office)
+pub fn matches_category(app: &AppEntry, filter: &str) -> bool {
+    if let Some((_, targets)) = CATEGORY_ALIASES.iter().find(|(alias, _)| *alias == filter) {
+        return app.categories.iter().any(|c| targets.contains(&c.as_str()));
+    }
+
+    app.categories
+        .iter()
+        .any(|c| c.to_lowercase().starts_with(filter))
+}
+
+// ============================================================================
+// DIRECTORY BROWSING
+// ============================================================================
+
+#[derive(Clone, Debug)]
+pub struct BrowseEntry {
+    pub name: String,
+    pub path: String,
+    pub is_dir: bool,
+}
+
+/// List a single directory level - directories first, then files, alphabetically
+pub fn list_directory(dir: &Path) -> Vec<BrowseEntry> {
+    let mut entries: Vec<BrowseEntry> = match fs::read_dir(dir) {
+        Ok(read_dir) => read_dir
+            .flatten()
+            .map(|entry| {
+                let path = entry.path();
+                BrowseEntry {
+                    name: entry.file_name().to_string_lossy().to_string(),
+                    is_dir: path.is_dir(),
+                    path: path.to_string_lossy().to_string(),
+                }
+            })
+            .collect(),
+        Err(_) => Vec::new(),
+    };
+
+    entries.sort_by(|a, b| {
+        b.is_dir
+            .cmp(&a.is_dir)
+            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+    });
+
+    entries
+}
+
+pub fn fuzzy_search_entries<'a>(query: &str, entries: &'a [BrowseEntry]) -> Vec<&'a BrowseEntry> {
+    let matcher = QueryMatcher::parse(query);
+
+    let mut matches: Vec<_> = entries
+        .iter()
+        .filter_map(|entry| matcher.score(&entry.name).map(|s| (s, entry)))
+        .collect();
+
+    // Stable sort keeps directories ahead of files on equal scores
+    matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+    matches.into_iter().map(|(_, e)| e).collect()
+}
+
+// ============================================================================
+// ZOXIDE DIRECTORY JUMPING
+// ============================================================================
+
+/// Frecent directories from zoxide, already ordered by its score
+pub fn zoxide_dirs() -> Vec<String> {
+    let output = match Command::new("zoxide").args(["query", "-l"]).output() {
+        Ok(output) if output.status.success() => output,
+        _ => return Vec::new(),
+    };
+
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(|line| line.to_string())
+        .collect()
+}
+
+/// Bump a directory's rank in zoxide after jumping to it
+pub fn zoxide_add(dir: &str) {
+    let _ = Command::new("zoxide").args(["add", dir]).status();
+}
+
+// ============================================================================
+// FILE ACTIONS
+// ============================================================================
+
+/// Put `text` on the Wayland or X11 clipboard; false if no tool is installed
+pub fn copy_to_clipboard(text: &str) -> bool {
+    use std::io::Write;
+    use std::process::Stdio;
+
+    let tools: [(&str, &[&str]); 3] = [
+        ("wl-copy", &[]),
+        ("xclip", &["-selection", "clipboard"]),
+        ("xsel", &["--clipboard", "--input"]),
+    ];
+    let wayland = env::var_os("WAYLAND_DISPLAY").is_some();
+    let Some((program, args)) = tools
+        .into_iter()
+        .filter(|(program, _)| wayland || *program != "wl-copy")
+        .find(|(program, _)| is_on_path(program))
+    else {
+        return false;
+    };
+
+    // The tools fork to keep serving the selection once stdin is closed
+    let child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn();
+    match child {
+        Ok(mut child) => {
+            if let Some(mut stdin) = child.stdin.take() {
+                let _ = stdin.write_all(text.as_bytes());
+            }
+            child.wait().is_ok_and(|status| status.success())
+        }
+        Err(_) => false,
+    }
+}
+
+/// Whether a file belongs in the editor rather than its default application.
+/// Asks `xdg-mime` for the MIME type, sniffing for NUL bytes when it can't tell.
+pub fn is_text_file(path: &str) -> bool {
+    match mime_type(path) {
+        Some(mime) if mime != "application/octet-stream" => is_text_mime(&mime),
+        _ => {
+            let mut head = [0u8; BINARY_SNIFF_BYTES];
+            let read = fs::File::open(path).and_then(|mut f| io::Read::read(&mut f, &mut head));
+            read.is_ok_and(|n| !looks_binary(&head[..n]))
+        }
+    }
+}
+
+fn mime_type(path: &str) -> Option<String> {
+    let output = Command::new("xdg-mime").args(["query", "filetype", path]).output().ok()?;
+    let mime = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    // Some backends append "; charset=..."
+    let mime = mime.split(';').next().unwrap_or("").trim().to_string();
+    (output.status.success() && !mime.is_empty()).then_some(mime)
+}
+
+fn is_text_mime(mime: &str) -> bool {
+    const TEXT_APPLICATION_TYPES: &[&str] = &[
+        "application/json",
+        "application/xml",
+        "application/javascript",
+        "application/toml",
+        "application/yaml",
+        "application/x-yaml",
+        "application/sql",
+        "application/x-shellscript",
+        "application/x-desktop",
+        "application/x-perl",
+        "application/x-ruby",
+        "application/x-php",
+        "application/x-zerosize",
+        "inode/x-empty",
+    ];
+
+    mime.starts_with("text/")
+        || TEXT_APPLICATION_TYPES.contains(&mime)
+        || (mime.starts_with("application/") && (mime.ends_with("+xml") || mime.ends_with("+json")))
+}
+
+/// Most lines, and bytes, read for the Files mode preview pane
+const PREVIEW_LINES: usize = 200;
+const PREVIEW_MAX_BYTES: u64 = 64 * 1024;
+
+/// First lines of a file for the preview pane, or a one-line note when it's
+/// binary or unreadable. Reads at most PREVIEW_MAX_BYTES.
+pub fn read_preview(path: &str) -> Vec<String> {
+    use std::io::Read;
+
+    let mut bytes = Vec::new();
+    let read = fs::File::open(path).and_then(|f| f.take(PREVIEW_MAX_BYTES).read_to_end(&mut bytes));
+    if let Err(e) = read {
+        return vec![format!("Can't read file: {}", e)];
+    }
+    if looks_binary(&bytes) {
+        return vec!["Binary file".to_string()];
+    }
+
+    String::from_utf8_lossy(&bytes)
+        .lines()
+        .take(PREVIEW_LINES)
+        .map(|line| line.replace('\t', "    "))
+        .collect()
+}
+
+/// Move a file to the freedesktop trash via `gio` or `trash-cli`
+pub fn move_to_trash(path: &str) -> io::Result<()> {
+    let command: &[&str] = if is_on_path("gio") {
+        &["gio", "trash", "--"]
+    } else if is_on_path("trash-put") {
+        &["trash-put", "--"]
+    } else {
+        return Err(io::Error::new(io::ErrorKind::NotFound, "neither gio nor trash-put is installed"));
+    };
+
+    let status = Command::new(command[0])
+        .args(&command[1..])
+        .arg(path)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status()?;
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!("{} exited with {}", command[0], status)))
+    }
+}
+
+/// Command that shows `path` selected in the file manager, falling back to
+/// opening its parent directory when there's no D-Bus FileManager1 to ask
+pub fn reveal_command(path: &str) -> (String, Vec<String>) {
+    if is_on_path("dbus-send") {
+        let args = [
+            "--session",
+            "--dest=org.freedesktop.FileManager1",
+            "--type=method_call",
+            "/org/freedesktop/FileManager1",
+            "org.freedesktop.FileManager1.ShowItems",
+        ];
+        let mut args: Vec<String> = args.iter().map(|a| a.to_string()).collect();
+        args.push(format!("array:string:{}", file_uri(path)));
+        args.push("string:".to_string());
+        return ("dbus-send".to_string(), args);
+    }
+
+    let parent = Path::new(path).parent().unwrap_or(Path::new("/"));
+    ("xdg-open".to_string(), vec![parent.to_string_lossy().to_string()])
+}
+
+/// `file://` URI for an absolute path, percent-encoding everything but
+/// unreserved characters and separators
+fn file_uri(path: &str) -> String {
+    let mut uri = String::from("file://");
+    for byte in path.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~/".contains(&byte) {
+            uri.push(byte as char);
+        } else {
+            uri.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    uri
+}
+
+// ============================================================================
+// COMPOSITOR WORKSPACES
+// ============================================================================
+
+/// Switch the compositor to `workspace` right after a launch, so the new
+/// window maps there. Only Hyprland and Sway are supported; others are a no-op.
+pub fn switch_workspace(workspace: &str) {
+    let (program, args): (&str, [&str; 2]) = if env::var_os("HYPRLAND_INSTANCE_SIGNATURE").is_some() {
+        ("hyprctl", ["dispatch", "workspace"])
+    } else if env::var_os("SWAYSOCK").is_some() {
+        ("swaymsg", ["workspace", "--no-auto-back-and-forth"])
+    } else {
+        return;
+    };
+    let _ = Command::new(program)
+        .args(args)
+        .arg(workspace)
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .status();
+}
+
+pub fn fuzzy_search_paths(query: &str, paths: &[String], limit: usize) -> Vec<String> {
+    use rayon::prelude::*;
+
+    let matcher = QueryMatcher::parse(query);
+
+    // par_iter().collect() preserves input order, so equal scores keep the source ranking
+    let mut matches: Vec<(i64, &String)> = paths
+        .par_iter()
+        .filter_map(|path| matcher.score(path).map(|s| (s, path)))
+        .collect();
+
+    matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+    matches.into_iter().take(limit).map(|(_, p)| p.clone()).collect()
+}
+
+pub fn fuzzy_search_history<'a>(query: &str, history: &'a [HistoryEntry]) -> Vec<&'a HistoryEntry> {
+    let matcher = QueryMatcher::parse(query);
+
+    let mut matches: Vec<_> = history
+        .iter()
+        .filter_map(|entry| matcher.score(&entry.command_line()).map(|s| (s, entry)))
+        .collect();
+
+    // Stable sort keeps the most recent launch first on equal scores
+    matches.sort_by_key(|m| std::cmp::Reverse(m.0));
+    matches.into_iter().map(|(_, e)| e).collect()
+}
+
+// ============================================================================
+// DATABASE SEEDING
+// ============================================================================
+
+/// A package manager that can list the binaries of explicitly installed packages
+pub trait SeedBackend {
+    /// Display name used in progress output
+    fn name(&self) -> &'static str;
+
+    /// Tool whose presence on $PATH identifies this package manager
+    fn probe(&self) -> &'static str;
+
+    /// Shell pipeline printing installed files, one per line (path as the last field)
+    fn file_list_command(&self) -> &'static str;
+
+    /// Whether a listed file is an executable worth seeding
+    fn is_binary_path(&self, path: &str) -> bool {
+        path.contains("/usr/bin/")
+    }
+
+    /// Names of binaries shipped by explicitly installed packages
+    fn installed_binaries(&self) -> io::Result<Vec<String>> {
+        let output = Command::new("sh")
+            .arg("-c")
+            .arg(self.file_list_command())
+            .output()?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let binaries = stdout
+            .lines()
+            .filter_map(|line| line.split_whitespace().last())
+            // Package file lists include the bin directory itself
+            .filter(|path| self.is_binary_path(path) && !path.ends_with('/'))
+            .filter_map(|path| Path::new(path).file_name())
+            .map(|name| name.to_string_lossy().to_string())
+            .collect();
+
+        Ok(binaries)
+    }
+}
+
+struct Pacman;
+struct Apt;
+struct Dnf;
+struct Zypper;
+struct Xbps;
+
+impl SeedBackend for Pacman {
+    fn name(&self) -> &'static str {
+        "Pacman"
+    }
+    fn probe(&self) -> &'static str {
+        "pacman"
+    }
+    fn file_list_command(&self) -> &'static str {
+        "pacman -Qqe | xargs pacman -Ql | grep '/usr/bin/'"
+    }
+}
+
+impl SeedBackend for Apt {
+    fn name(&self) -> &'static str {
+        "APT"
+    }
+    fn probe(&self) -> &'static str {
+        "apt-mark"
+    }
+    fn file_list_command(&self) -> &'static str {
+        "apt-mark showmanual | xargs dpkg -L 2>/dev/null | grep '^/usr/bin/'"
+    }
+}
+
+impl SeedBackend for Dnf {
+    fn name(&self) -> &'static str {
+        "DNF"
+    }
+    fn probe(&self) -> &'static str {
+        "dnf"
+    }
+    fn file_list_command(&self) -> &'static str {
+        "dnf repoquery --userinstalled --queryformat '%{name}\n' 2>/dev/null | xargs rpm -ql | grep '^/usr/bin/'"
+    }
+}
+
+impl SeedBackend for Zypper {
+    fn name(&self) -> &'static str {
+        "Zypper"
+    }
+    fn probe(&self) -> &'static str {
+        "zypper"
+    }
+    fn file_list_command(&self) -> &'static str {
+        // zypp records dependency-only installs; everything else was asked for
+        "rpm -qa --queryformat '%{NAME}\n' | grep -vxF -f /var/lib/zypp/AutoInstalled | xargs rpm -ql | grep '^/usr/bin/'"
+    }
+}
+
+impl SeedBackend for Xbps {
+    fn name(&self) -> &'static str {
+        "XBPS"
+    }
+    fn probe(&self) -> &'static str {
+        "xbps-query"
+    }
+    fn file_list_command(&self) -> &'static str {
+        "xbps-query -m | xargs -n1 xbps-query -f | grep '^/usr/bin/'"
+    }
+}
+
+/// Homebrew/Linuxbrew formulae - opt-in via `--seed-brew`, alongside the system backend
+struct Homebrew;
+
+impl SeedBackend for Homebrew {
+    fn name(&self) -> &'static str {
+        "Homebrew"
+    }
+    fn probe(&self) -> &'static str {
+        "brew"
+    }
+    fn file_list_command(&self) -> &'static str {
+        "brew list --formula | xargs brew list --formula 2>/dev/null | grep '/bin/'"
+    }
+    fn is_binary_path(&self, path: &str) -> bool {
+        // Formula files live in the Cellar, e.g. .../Cellar/ripgrep/14.1.0/bin/rg
+        path.contains("/bin/")
+    }
+}
+
+/// First supported package manager found on $PATH
+fn detect_seed_backend() -> Option<Box<dyn SeedBackend>> {
+    let backends: Vec<Box<dyn SeedBackend>> = vec![
+        Box::new(Pacman),
+        Box::new(Xbps),
+        Box::new(Zypper),
+        Box::new(Dnf),
+        Box::new(Apt),
+    ];
+
+    backends.into_iter().find(|b| is_on_path(b.probe()))
+}
+
+pub fn is_on_path(binary: &str) -> bool {
+    env::var("PATH")
+        .map(|path| path.split(':').any(|dir| Path::new(dir).join(binary).is_file()))
+        .unwrap_or(false)
+}
+
+pub fn seed_database(db: &Database) {
+    let backend = match detect_seed_backend() {
+        Some(b) => b,
+        None => {
+            println!("No supported package manager found (pacman, xbps, zypper, dnf, apt).");
+            return;
+        }
+    };
+
+    seed_from_backend(db, backend.as_ref());
+}
+
+pub fn seed_database_brew(db: &Database) {
+    if !is_on_path(Homebrew.probe()) {
+        println!("Homebrew not found on $PATH.");
+        return;
+    }
+
+    seed_from_backend(db, &Homebrew);
+}
+
+fn seed_from_backend(db: &Database, backend: &dyn SeedBackend) {
+    println!(
+        "Seeding database from {}... this might take a few seconds.",
+        backend.name()
+    );
+
+    let binaries = match backend.installed_binaries() {
+        Ok(b) => b,
+        Err(e) => {
+            println!("Failed to query {}: {}", backend.name(), e);
+            return;
+        }
+    };
+
+    let mut count = 0;
+    for name in binaries {
+        let _ = db.set_base_score(&name, 50);
+        count += 1;
+    }
+
+    println!("Seeded {} apps with +50 score.", count);
+}
+
+// ============================================================================
+// DATABASE MAINTENANCE
+// ============================================================================
+
+/// Drop the stats of apps that are no longer installed and haven't been
+/// launched for `idle_days`, so uninstalled apps stop piling up in app_prefs
+pub fn prune_database(db: &Database, config: &Config, idle_days: u64) {
+    println!("Scanning installed apps...");
+    let installed: HashSet<String> = with_config_entries(scan_apps_fresh(db, &config.apps), config, db)
+        .into_iter()
+        .map(|app| app.name)
+        .collect();
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+    let idle_before = now.saturating_sub(idle_days * 24 * 60 * 60);
+
+    match db.prune_apps(&installed, idle_before) {
+        Ok(removed) if removed.is_empty() => println!("Nothing to prune."),
+        Ok(removed) => {
+            for name in &removed {
+                println!("  {}", name);
+            }
+            println!("Removed {} stale entries.", removed.len());
+        }
+        Err(e) => println!("Failed to prune: {}", e),
+    }
+}
+
+/// Reset the stats of apps dormant for `apps.dormant_days`, so they rank as new
+pub fn reset_dormant(db: &Database, config: &Config) {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_secs();
+
+    match db.reset_idle_apps(now.saturating_sub(config.apps.dormant_secs())) {
+        Ok(reset) if reset.is_empty() => println!("No dormant apps."),
+        Ok(reset) => {
+            for name in &reset {
+                println!("  {}", name);
+            }
+            println!("Reset {} dormant apps.", reset.len());
+        }
+        Err(e) => println!("Failed to reset: {}", e),
+    }
 }
@@ -3,64 +3,234 @@
 // ============================================================================
 
 use std::io::{self, Write};
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use crossterm::{
     cursor::{MoveTo, Show, Hide},
-    terminal::{Clear, ClearType, size},
+    event::{KeyboardEnhancementFlags, PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags},
+    terminal::{
+        BeginSynchronizedUpdate, Clear, ClearType, EndSynchronizedUpdate, EnterAlternateScreen,
+        LeaveAlternateScreen, disable_raw_mode, enable_raw_mode, size,
+    },
     QueueableCommand,
 };
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 use crate::theme::*;
 
+/// Set while rula's screen is up, so whichever of cleanup() and the panic
+/// hook runs first tears it down and the other does nothing
+static SCREEN_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Hand the screen back as it was: keyboard flags, colors, cursor and the main screen
+fn leave_screen() -> io::Result<()> {
+    if !SCREEN_ACTIVE.swap(false, Ordering::SeqCst) {
+        return Ok(());
+    }
+    let mut stdout = io::stdout();
+    stdout.queue(PopKeyboardEnhancementFlags)?;
+    write!(stdout, "{}{}", RESET, CURSOR_DEFAULT)?;
+    stdout.queue(Show)?;
+    stdout.queue(LeaveAlternateScreen)?;
+    stdout.flush()
+}
+
+/// Raw mode for as long as it lives. Creating one also installs a panic hook
+/// that restores the terminal before the panic message is printed.
+pub struct RawModeGuard;
+
+impl RawModeGuard {
+    pub fn new() -> io::Result<Self> {
+        let default_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            let _ = leave_screen();
+            let _ = disable_raw_mode();
+            default_hook(info);
+        }));
+        enable_raw_mode()?;
+        Ok(Self)
+    }
+}
+
+impl Drop for RawModeGuard {
+    fn drop(&mut self) {
+        let _ = disable_raw_mode();
+    }
+}
+
+/// One screen cell: a grapheme and the SGR sequences it is drawn with. The
+/// cells a wide grapheme spills into hold an empty one.
+#[derive(Clone, PartialEq, Eq)]
+struct Cell {
+    symbol: Rc<str>,
+    style: Rc<str>,
+}
+
+/// Where frames go: the real terminal, or nowhere for headless tests that
+/// read the cells back instead
+enum Backend {
+    Stdout(io::Stdout),
+    #[cfg(test)]
+    Memory,
+}
+
 pub struct Terminal {
-    stdout: io::Stdout,
+    backend: Backend,
     width: u16,
     height: u16,
+    theme: Theme,
+    // Leave the terminal's background (and its transparency) alone
+    transparent: bool,
+    // Frame being drawn, and the one last sent to the terminal
+    back: Vec<Cell>,
+    front: Vec<Cell>,
+    // Where write() puts the next character
+    cursor: (u16, u16),
+    // Shown text cursor: position and shape escape
+    text_cursor: Option<(u16, u16, &'static str)>,
+    // Front no longer matches the screen, so the next flush repaints everything
+    full_redraw: bool,
 }
 
 impl Terminal {
-    pub fn new() -> io::Result<Self> {
+    pub fn new(theme: Theme, transparent: bool) -> io::Result<Self> {
         let (width, height) = size()?;
         let mut term = Self {
-            stdout: io::stdout(),
+            backend: Backend::Stdout(io::stdout()),
             width,
             height,
+            theme,
+            transparent,
+            back: Vec::new(),
+            front: Vec::new(),
+            cursor: (0, 0),
+            text_cursor: None,
+            full_redraw: true,
         };
         term.setup()?;
         Ok(term)
     }
 
+    /// A terminal of a fixed size that keeps its frames in memory
+    #[cfg(test)]
+    pub fn headless(theme: Theme, width: u16, height: u16) -> Self {
+        let mut term = Self {
+            backend: Backend::Memory,
+            width,
+            height,
+            theme,
+            transparent: false,
+            back: Vec::new(),
+            front: Vec::new(),
+            cursor: (0, 0),
+            text_cursor: None,
+            full_redraw: true,
+        };
+        term.clear();
+        term
+    }
+
+    /// The real terminal, or None when headless
+    fn stdout(&mut self) -> Option<&mut io::Stdout> {
+        match &mut self.backend {
+            Backend::Stdout(stdout) => Some(stdout),
+            #[cfg(test)]
+            Backend::Memory => None,
+        }
+    }
+
     fn setup(&mut self) -> io::Result<()> {
-        // Hide cursor and clear screen
-        self.stdout.queue(Hide)?;
-        self.clear()?;
+        if let Some(stdout) = self.stdout() {
+            // Draw on the alternate screen so the shell and its scrollback come back on exit
+            stdout.queue(EnterAlternateScreen)?;
+            SCREEN_ACTIVE.store(true, Ordering::SeqCst);
+            // Lets kitty and other modern terminals report Ctrl/Shift+Enter; others ignore it.
+            // Kitty keeps one flag stack per screen, so this has to follow the switch.
+            stdout.queue(PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES))?;
+            // Hide cursor and clear screen
+            stdout.queue(Hide)?;
+        }
+        self.clear();
         self.flush()
     }
 
-    pub fn clear(&mut self) -> io::Result<()> {
-        // Fill entire screen with base color
-        self.stdout.queue(Clear(ClearType::All))?;
-        self.fill_background()?;
+    /// Start a new frame: every cell blank, on the base color unless transparent
+    pub fn clear(&mut self) {
+        let blank = self.blank();
+        self.back.clear();
+        self.back.resize(self.width as usize * self.height as usize, blank);
+        self.text_cursor = None;
+    }
+
+    fn blank(&self) -> Cell {
+        // Transparent blanks keep the terminal's default background
+        let style: Rc<str> = if self.transparent { Rc::from(RESET_BG) } else { Rc::from(self.theme.base.bg()) };
+        Cell { symbol: Rc::from(" "), style }
+    }
+
+    pub fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
+        self.cursor = (x, y);
         Ok(())
     }
 
-    fn fill_background(&mut self) -> io::Result<()> {
-        // Fill the screen with base background color
-        let bg = RosePineMoon::BASE.bg();
-        let reset = RESET;
+    /// Put text into the frame at the write position. Only SGR escapes are
+    /// understood; each call starts from the terminal's default style.
+    pub fn write(&mut self, text: &str) -> io::Result<()> {
+        let (mut x, y) = self.cursor;
+        let mut style: Rc<str> = Rc::from("");
+        let mut rest = text;
+
+        while !rest.is_empty() {
+            let plain = rest.find('\x1b').unwrap_or(rest.len());
+            for grapheme in rest[..plain].graphemes(true) {
+                let width = grapheme.width();
+                if width == 0 || grapheme.chars().any(char::is_control) {
+                    continue;
+                }
+                self.put(x, y, grapheme, width, &style);
+                x = x.saturating_add(width as u16);
+            }
+            rest = &rest[plain..];
 
-        for y in 0..self.height {
-            self.stdout.queue(MoveTo(0, y))?;
-            write!(self.stdout, "{}{}", bg, " ".repeat(self.width as usize))?;
+            // ESC [ parameters, up to a final byte in @..~
+            let Some((end, last)) = rest.char_indices().skip(2).find(|(_, c)| ('\x40'..='\x7e').contains(c)) else {
+                break;
+            };
+            let seq = &rest[..end + last.len_utf8()];
+            if seq.starts_with("\x1b[") && last == 'm' {
+                style = if seq == RESET || seq == "\x1b[m" {
+                    Rc::from("")
+                } else {
+                    Rc::from(format!("{}{}", style, seq))
+                };
+            }
+            rest = &rest[seq.len()..];
         }
-        write!(self.stdout, "{}", reset)?;
+        self.cursor = (x, y);
         Ok(())
     }
 
-    pub fn move_to(&mut self, x: u16, y: u16) -> io::Result<()> {
-        self.stdout.queue(MoveTo(x, y)).map(|_| ())
-    }
+    /// Place a grapheme `width` columns wide; it is dropped if it would not fit
+    fn put(&mut self, x: u16, y: u16, grapheme: &str, width: usize, style: &Rc<str>) {
+        if y >= self.height || x as usize + width > self.width as usize {
+            return;
+        }
+        let row_end = (y as usize + 1) * self.width as usize;
+        let i = y as usize * self.width as usize + x as usize;
+        let after = i + width;
 
-    pub fn write(&mut self, text: &str) -> io::Result<()> {
-        write!(self.stdout, "{}", text)
+        // Overwriting half of a wide grapheme blanks the other half
+        if x > 0 && self.back[i].symbol.is_empty() {
+            self.back[i - 1].symbol = Rc::from(" ");
+        }
+        if after < row_end && self.back[after].symbol.is_empty() {
+            self.back[after].symbol = Rc::from(" ");
+        }
+
+        self.back[i] = Cell { symbol: Rc::from(grapheme), style: style.clone() };
+        for cell in &mut self.back[i + 1..after] {
+            *cell = Cell { symbol: Rc::from(""), style: style.clone() };
+        }
     }
 
     pub fn write_at(&mut self, x: u16, y: u16, text: &str) -> io::Result<()> {
@@ -76,7 +246,7 @@ impl Terminal {
     /// Draw a horizontal line with a specific character and color
     pub fn hline(&mut self, x: u16, y: u16, width: u16, ch: char, color: Color) -> io::Result<()> {
         self.move_to(x, y)?;
-        let line: String = std::iter::repeat(ch).take(width as usize).collect();
+        let line: String = std::iter::repeat_n(ch, width as usize).collect();
         self.write(&styled(&line, color))
     }
 
@@ -85,7 +255,7 @@ impl Terminal {
     pub fn hline_bg(&mut self, x: u16, y: u16, width: u16, bg: Color) -> io::Result<()> {
         self.move_to(x, y)?;
         let spaces = " ".repeat(width as usize);
-        self.write(&styled_bg(&spaces, RosePineMoon::MUTED, bg))
+        self.write(&styled_bg(&spaces, self.theme.muted, bg))
     }
 
     /// Clear a line and fill with background color
@@ -93,21 +263,98 @@ impl Terminal {
     pub fn clear_line_bg(&mut self, y: u16, bg: Color) -> io::Result<()> {
         self.move_to(0, y)?;
         let spaces = " ".repeat(self.width as usize);
-        self.write(&styled_bg(&spaces, RosePineMoon::TEXT, bg))
+        self.write(&styled_bg(&spaces, self.theme.text, bg))
     }
 
+    /// Show the text cursor at a position with a CURSOR_* shape once the frame is flushed
+    pub fn place_cursor(&mut self, x: u16, y: u16, shape: &'static str) {
+        self.text_cursor = Some((x, y, shape));
+    }
+
+    /// Send the frame, writing only the cells that changed since the last one
     pub fn flush(&mut self) -> io::Result<()> {
-        self.stdout.flush()
+        let mut out: Vec<u8> = Vec::new();
+        out.queue(BeginSynchronizedUpdate)?;
+        out.queue(Hide)?;
+        if self.full_redraw {
+            write!(out, "{}", RESET)?;
+            out.queue(Clear(ClearType::All))?;
+        }
+
+        let width = self.width as usize;
+        // Style last emitted, and where the terminal's cursor sits after the last cell
+        let mut current: Option<&str> = None;
+        let mut at: Option<(usize, usize)> = None;
+        for (i, cell) in self.back.iter().enumerate() {
+            if !self.full_redraw && self.front.get(i) == Some(cell) {
+                continue;
+            }
+            // Drawn along with the wide grapheme before it
+            if cell.symbol.is_empty() {
+                continue;
+            }
+            let (x, y) = (i % width, i / width);
+            if at != Some((x, y)) {
+                out.queue(MoveTo(x as u16, y as u16))?;
+            }
+            if current != Some(&*cell.style) {
+                write!(out, "{}{}", RESET, cell.style)?;
+                current = Some(&cell.style);
+            }
+            write!(out, "{}", cell.symbol)?;
+            at = Some((x + cell.symbol.width(), y));
+        }
+        write!(out, "{}", RESET)?;
+
+        if let Some((x, y, shape)) = self.text_cursor {
+            write!(out, "{}{}", SHOW_CURSOR, shape)?;
+            out.queue(MoveTo(x, y))?;
+        }
+        out.queue(EndSynchronizedUpdate)?;
+
+        if let Some(stdout) = self.stdout() {
+            stdout.write_all(&out)?;
+            stdout.flush()?;
+        }
+        self.front.clone_from(&self.back);
+        self.full_redraw = false;
+        Ok(())
     }
 
     pub fn size(&self) -> (u16, u16) {
         (self.width, self.height)
     }
 
+    /// Take on a new terminal size; the next frame repaints all of it
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.width = width;
+        self.height = height;
+        self.full_redraw = true;
+        self.clear();
+    }
+
+    /// Text of each row of the last flushed frame, without styles or trailing blanks
+    #[cfg(test)]
+    pub fn rows(&self) -> Vec<String> {
+        self.front
+            .chunks(self.width as usize)
+            .map(|row| row.iter().map(|cell| &*cell.symbol).collect::<String>().trim_end().to_string())
+            .collect()
+    }
+
+    /// Where the text cursor was shown in the last frame
+    #[cfg(test)]
+    pub fn text_cursor(&self) -> Option<(u16, u16)> {
+        self.text_cursor.map(|(x, y, _)| (x, y))
+    }
+
+    /// Hand the terminal back as it was: default colors, cursor and main screen
     pub fn cleanup(&mut self) -> io::Result<()> {
-        self.stdout.queue(Show)?;
-        self.stdout.queue(Clear(ClearType::All))?;
-        self.flush()
+        match self.backend {
+            Backend::Stdout(_) => leave_screen(),
+            #[cfg(test)]
+            Backend::Memory => Ok(()),
+        }
     }
 }
 
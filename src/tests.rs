@@ -0,0 +1,252 @@
+// ============================================================================
+// Headless Tests - App, Ui and InputHandler driven by synthetic key events,
+// with each frame read back from an in-memory terminal
+// ============================================================================
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use crate::app::{App, InputMode};
+use crate::config::Config;
+use crate::db::{Database, LaunchEvent};
+use crate::input::InputHandler;
+use crate::system::AppEntry;
+use crate::ui::Ui;
+
+struct Harness {
+    app: App,
+    ui: Ui,
+    input: InputHandler,
+}
+
+impl Harness {
+    fn new(names: &[&str], width: u16, height: u16) -> Self {
+        let config = Config::default();
+        let apps = names.iter().map(|name| app_entry(name)).collect();
+        Self {
+            ui: Ui::headless(&config, width, height),
+            input: InputHandler::new(&config.keys),
+            app: App::headless(config, apps),
+        }
+    }
+
+    fn press(&mut self, code: KeyCode) {
+        self.input.process(&mut self.app, KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    /// Each char as its own key press
+    fn type_keys(&mut self, keys: &str) {
+        for c in keys.chars() {
+            self.press(KeyCode::Char(c));
+        }
+    }
+
+    /// Render a frame and return its rows
+    fn screen(&mut self) -> Vec<String> {
+        self.ui.render(&self.app).unwrap();
+        self.ui.terminal().rows()
+    }
+
+    fn cursor(&mut self) -> (u16, u16) {
+        self.ui.render(&self.app).unwrap();
+        self.ui.terminal().text_cursor().unwrap()
+    }
+}
+
+fn app_entry(name: &str) -> AppEntry {
+    AppEntry {
+        name: name.to_string(),
+        generic_name: String::new(),
+        keywords: Vec::new(),
+        comment: String::new(),
+        categories: Vec::new(),
+        exec: name.to_lowercase(),
+        is_cli_only: false,
+        is_action: false,
+        is_terminal: false,
+        icon: String::new(),
+        desktop_path: String::new(),
+        flatpak_id: String::new(),
+        total_score: 0,
+        is_dormant: false,
+    }
+}
+
+/// The result rows, with the border and gutter stripped
+fn results(screen: &[String]) -> Vec<String> {
+    screen[3..screen.len() - 1]
+        .iter()
+        .map(|row| row.chars().skip(2).collect::<String>().trim_end_matches(['│', ' ']).to_string())
+        .filter(|row| !row.is_empty())
+        .collect()
+}
+
+#[test]
+fn renders_frame() {
+    let mut h = Harness::new(&["Firefox", "Kitty"], 40, 7);
+    assert_eq!(
+        h.screen(),
+        [
+            "╭──────────────────────────────────────╮",
+            "│ Apps >                               │",
+            "│                                      │",
+            "│ > Firefox                            │",
+            "│   Kitty                              │",
+            "│                                      │",
+            "╰─ Apps  INSERT ─ 2/2 matches ─────────╯",
+        ]
+    );
+}
+
+#[test]
+fn typing_filters_results() {
+    let mut h = Harness::new(&["Firefox", "Files", "Kitty"], 60, 10);
+    h.type_keys("kit");
+    let screen = h.screen();
+    assert_eq!(h.app.input, "kit");
+    assert!(screen[1].contains("Apps > kit"));
+    assert_eq!(results(&screen).len(), 1);
+    assert!(results(&screen)[0].ends_with("Kitty"));
+    assert!(screen[9].contains("1/3 matches"));
+}
+
+#[test]
+fn growing_query_refines_then_widens() {
+    let mut h = Harness::new(&["Firefox", "Files", "Kitty"], 60, 10);
+    h.type_keys("fi");
+    assert_eq!(results(&h.screen()).len(), 2);
+    h.type_keys("r");
+    assert_eq!(results(&h.screen()).len(), 1);
+    h.press(KeyCode::Backspace);
+    assert_eq!(results(&h.screen()).len(), 2);
+
+    // A longer regex can match more, so it is never narrowed from the last results
+    h.press(KeyCode::Esc);
+    h.type_keys("cc");
+    h.type_keys("/fi");
+    assert_eq!(results(&h.screen()).len(), 2);
+    h.type_keys("|kit");
+    assert_eq!(results(&h.screen()).len(), 3);
+}
+
+#[test]
+fn normal_mode_moves_selection() {
+    let mut h = Harness::new(&["Alpha", "Beta", "Gamma"], 60, 10);
+    h.press(KeyCode::Esc);
+    h.type_keys("jj");
+    assert_eq!(h.app.input_mode, InputMode::Normal);
+    assert_eq!(h.app.selected_index, 2);
+    assert!(results(&h.screen())[2].starts_with("> "));
+
+    h.type_keys("k");
+    assert!(results(&h.screen())[1].starts_with("> "));
+}
+
+#[test]
+fn vim_operators_edit_query() {
+    let mut h = Harness::new(&["Alpha"], 60, 10);
+    h.type_keys("open the door");
+    h.press(KeyCode::Esc);
+    h.type_keys("bdb");
+    assert_eq!(h.app.input, "open door");
+
+    h.type_keys("ciwwindow");
+    assert_eq!(h.app.input, "open window");
+    assert_eq!(h.app.input_mode, InputMode::Insert);
+}
+
+#[test]
+fn cursor_counts_display_columns() {
+    let mut h = Harness::new(&["Alpha"], 60, 10);
+    let (start, row) = h.cursor();
+    assert_eq!(row, 1);
+
+    // Wide characters take two columns, a combining accent none
+    h.type_keys("日本e\u{301}");
+    assert_eq!(h.app.cursor_pos, 3);
+    assert_eq!(h.cursor(), (start + 5, 1));
+
+    h.press(KeyCode::Backspace);
+    assert_eq!(h.app.input, "日本");
+    assert_eq!(h.cursor(), (start + 4, 1));
+}
+
+#[test]
+fn enter_launches_selection() {
+    let mut h = Harness::new(&["Firefox", "Kitty"], 60, 10);
+    h.type_keys("kitty");
+    h.press(KeyCode::Enter);
+    assert!(h.app.should_launch);
+    assert_eq!(h.app.take_launches()[0].0 .0, "kitty");
+}
+
+#[test]
+fn resize_relays_out() {
+    let mut h = Harness::new(&["Firefox"], 60, 10);
+    h.screen();
+    h.ui.resize(30, 6);
+    let screen = h.screen();
+    assert_eq!(screen.len(), 6);
+    assert_eq!(screen[0].chars().count(), 30);
+    assert!(screen[5].starts_with('╰') && screen[5].ends_with('╯'));
+}
+
+#[test]
+fn stats_count_failed_launches() {
+    let db = Database::in_memory().unwrap();
+    for (launched_at, success) in [(100, true), (200, false), (300, true)] {
+        let event = LaunchEvent {
+            app: "Kitty".to_string(),
+            mode: "apps".to_string(),
+            success,
+            launched_at,
+        };
+        db.log_launch_event(&event).unwrap();
+    }
+    assert_eq!(db.launch_times_since(150), [(200, false), (300, true)]);
+}
+
+#[test]
+fn exact_match_highlights_original_chars() {
+    // "İ" lowercases to two chars and "ẞ" to fewer bytes, which used to shift the highlight
+    let matcher = crate::system::QueryMatcher::parse("'stan");
+    assert_eq!(matcher.indices("İstanbul"), Some(vec![1, 2, 3, 4]));
+    assert_eq!(matcher.indices("GROẞE STANDUHR"), Some(vec![6, 7, 8, 9]));
+    assert!(matcher.score("İSTANBUL").is_some());
+}
+
+#[test]
+fn fuzzy_prefilter_ignores_diacritics() {
+    // The prefilter must keep every path the pattern would match
+    let matcher = crate::system::QueryMatcher::parse("cafe");
+    assert!(matcher.could_match("/home/me/Café.txt"));
+    assert!(matcher.score("/home/me/Café.txt").is_some());
+
+    let matcher = crate::system::QueryMatcher::parse("crème");
+    assert!(matcher.could_match("/home/me/creme brulee.md"));
+    assert!(!matcher.could_match("/home/me/notes.md"));
+}
+
+#[test]
+fn single_app_frecency_matches_batch() {
+    let db = Database::in_memory().unwrap();
+    for _ in 0..3 {
+        db.increment_usage("Kitty", false).unwrap();
+    }
+    db.increment_usage("Firefox", false).unwrap();
+
+    let all = db.get_all_frecency();
+    assert!(all["Kitty"] > 0);
+    assert_eq!(db.frecency("Kitty"), all["Kitty"]);
+    assert_eq!(db.frecency("Firefox"), all["Firefox"]);
+    assert_eq!(db.frecency("Unknown"), 0);
+}
+
+#[test]
+fn import_rejects_malformed_exports() {
+    let db = Database::in_memory().unwrap();
+    let bad_row = serde_json::json!({ "version": 1, "tables": { "app_prefs": ["Kitty"] } });
+    assert!(matches!(db.import_json(&bad_row), Err(crate::error::Error::ImportFormat(_))));
+    assert!(matches!(db.import_json(&serde_json::json!({})), Err(crate::error::Error::ImportFormat(_))));
+
+    let export = db.export_json().unwrap();
+    assert_eq!(db.import_json(&export).unwrap(), 1); // The meta row holding last_decay
+}
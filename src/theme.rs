@@ -1,7 +1,12 @@
 // ============================================================================
-// ROSE PINE MOON - Color Palette
+// THEME - Colors, palettes and styles
 // ============================================================================
 
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use crate::config::{expand_tilde, Config};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Color {
     pub r: u8,
@@ -14,6 +19,22 @@ impl Color {
         Self { r, g, b }
     }
 
+    /// Parse `#rrggbb` (the `#` is optional)
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+        Some(Self::new(channel(0)?, channel(2)?, channel(4)?))
+    }
+
+    /// Blend `amount` (0.0 - 1.0) of `other` into this color
+    pub fn mix(self, other: Color, amount: f32) -> Self {
+        let blend = |a: u8, b: u8| (a as f32 + (b as f32 - a as f32) * amount).round() as u8;
+        Self::new(blend(self.r, other.r), blend(self.g, other.g), blend(self.b, other.b))
+    }
+
     /// Convert to ANSI truecolor escape sequence (foreground)
     pub fn fg(&self) -> String {
         format!("\x1b[38;2;{};{};{}m", self.r, self.g, self.b)
@@ -29,48 +50,285 @@ impl Color {
 pub const RESET: &str = "\x1b[0m";
 #[allow(dead_code)]
 pub const RESET_FG: &str = "\x1b[39m";
-#[allow(dead_code)]
 pub const RESET_BG: &str = "\x1b[49m";
 #[allow(dead_code)]
 pub const CLEAR_SCREEN: &str = "\x1b[2J";
 #[allow(dead_code)]
 pub const CLEAR_LINE: &str = "\x1b[2K";
+#[allow(dead_code)]
 pub const HIDE_CURSOR: &str = "\x1b[?25l";
 pub const SHOW_CURSOR: &str = "\x1b[?25h";
+pub const CURSOR_BLOCK: &str = "\x1b[2 q";
+pub const CURSOR_BAR: &str = "\x1b[6 q";
+pub const CURSOR_DEFAULT: &str = "\x1b[0 q";
 #[allow(dead_code)]
 pub const CURSOR_HOME: &str = "\x1b[H";
 
-// Rose Pine Moon Palette
-pub struct RosePineMoon;
-
-impl RosePineMoon {
+/// Runtime palette, resolved once from config. Slots are named after Rose
+/// Pine's roles; other palettes map their colors onto the same roles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
     // Backgrounds - flowing from dark to light
-    pub const BASE: Color = Color::new(35, 33, 54);        // #232136 - Deepest background
-    #[allow(dead_code)]
-    pub const SURFACE: Color = Color::new(42, 39, 63);     // #2a273f - Slightly lifted
-    #[allow(dead_code)]
-    pub const OVERLAY: Color = Color::new(57, 53, 82);     // #393552 - Interactive elements
-    #[allow(dead_code)]
-    pub const HIGHLIGHT_LOW: Color = Color::new(42, 40, 62);   // #2a283e
-    pub const HIGHLIGHT_MED: Color = Color::new(68, 65, 90);   // #44415a
-    #[allow(dead_code)]
-    pub const HIGHLIGHT_HIGH: Color = Color::new(86, 82, 110); // #56526e
+    pub base: Color,           // Deepest background
+    pub surface: Color,        // Slightly lifted
+    pub overlay: Color,        // Interactive elements
+    pub highlight_low: Color,
+    pub highlight_med: Color,  // Borders
+    pub highlight_high: Color,
 
     // Foregrounds - flowing from muted to bright
-    pub const MUTED: Color = Color::new(110, 106, 134);    // #6e6a86 - Comments, hints
-    pub const SUBTLE: Color = Color::new(144, 140, 170);   // #908caa - Secondary text
-    pub const TEXT: Color = Color::new(224, 222, 244);     // #e0def4 - Primary text
+    pub muted: Color,          // Comments, hints
+    pub subtle: Color,         // Secondary text
+    pub text: Color,           // Primary text
 
     // Accents - each with a distinct purpose
-    pub const LOVE: Color = Color::new(235, 111, 146);     // #eb6f92 - Errors, quit
-    pub const GOLD: Color = Color::new(246, 193, 119);     // #f6c177 - Files mode, warnings
-    #[allow(dead_code)]
-    pub const ROSE: Color = Color::new(234, 154, 151);     // #ea9a97 - Soft highlights
-    pub const PINE: Color = Color::new(62, 143, 176);      // #3e8fb0 - Insert mode, TUI
-    #[allow(dead_code)]
-    pub const FOAM: Color = Color::new(156, 207, 216);     // #9ccfd8 - Apps mode, info
-    #[allow(dead_code)]
-    pub const IRIS: Color = Color::new(196, 167, 231);     // #c4a7e7 - Normal mode, hints
+    pub love: Color,           // Errors, Apps mode
+    pub gold: Color,           // Files mode, warnings
+    pub rose: Color,           // Soft highlights, matches
+    pub pine: Color,           // Grep mode, TUI
+    pub foam: Color,           // Info, confirmations
+    pub iris: Color,           // Jump mode, hints
+}
+
+impl Theme {
+    pub const ROSE_PINE_MOON: Theme = Theme {
+        base: Color::new(35, 33, 54),            // #232136
+        surface: Color::new(42, 39, 63),         // #2a273f
+        overlay: Color::new(57, 53, 82),         // #393552
+        highlight_low: Color::new(42, 40, 62),   // #2a283e
+        highlight_med: Color::new(68, 65, 90),   // #44415a
+        highlight_high: Color::new(86, 82, 110), // #56526e
+        muted: Color::new(110, 106, 134),        // #6e6a86
+        subtle: Color::new(144, 140, 170),       // #908caa
+        text: Color::new(224, 222, 244),         // #e0def4
+        love: Color::new(235, 111, 146),         // #eb6f92
+        gold: Color::new(246, 193, 119),         // #f6c177
+        rose: Color::new(234, 154, 151),         // #ea9a97
+        pine: Color::new(62, 143, 176),          // #3e8fb0
+        foam: Color::new(156, 207, 216),         // #9ccfd8
+        iris: Color::new(196, 167, 231),         // #c4a7e7
+    };
+
+    pub const ROSE_PINE_DAWN: Theme = Theme {
+        base: Color::new(250, 244, 237),         // #faf4ed
+        surface: Color::new(255, 250, 243),      // #fffaf3
+        overlay: Color::new(242, 233, 225),      // #f2e9e1
+        highlight_low: Color::new(244, 237, 232), // #f4ede8
+        highlight_med: Color::new(223, 218, 217), // #dfdad9
+        highlight_high: Color::new(206, 202, 205), // #cecacd
+        muted: Color::new(152, 147, 165),        // #9893a5
+        subtle: Color::new(121, 117, 147),       // #797593
+        text: Color::new(87, 82, 121),           // #575279
+        love: Color::new(180, 99, 122),          // #b4637a
+        gold: Color::new(234, 157, 52),          // #ea9d34
+        rose: Color::new(215, 130, 126),         // #d7827e
+        pine: Color::new(40, 105, 131),          // #286983
+        foam: Color::new(86, 148, 159),          // #56949f
+        iris: Color::new(144, 122, 169),         // #907aa9
+    };
+
+    pub const CATPPUCCIN_MOCHA: Theme = Theme {
+        base: Color::new(30, 30, 46),            // #1e1e2e
+        surface: Color::new(49, 50, 68),         // #313244
+        overlay: Color::new(69, 71, 90),         // #45475a
+        highlight_low: Color::new(49, 50, 68),   // #313244
+        highlight_med: Color::new(69, 71, 90),   // #45475a
+        highlight_high: Color::new(88, 91, 112), // #585b70
+        muted: Color::new(108, 112, 134),        // #6c7086
+        subtle: Color::new(166, 173, 200),       // #a6adc8
+        text: Color::new(205, 214, 244),         // #cdd6f4
+        love: Color::new(243, 139, 168),         // #f38ba8
+        gold: Color::new(249, 226, 175),         // #f9e2af
+        rose: Color::new(242, 205, 205),         // #f2cdcd
+        pine: Color::new(137, 180, 250),         // #89b4fa
+        foam: Color::new(148, 226, 213),         // #94e2d5
+        iris: Color::new(203, 166, 247),         // #cba6f7
+    };
+
+    pub const GRUVBOX: Theme = Theme {
+        base: Color::new(40, 40, 40),            // #282828
+        surface: Color::new(60, 56, 54),         // #3c3836
+        overlay: Color::new(80, 73, 69),         // #504945
+        highlight_low: Color::new(50, 48, 47),   // #32302f
+        highlight_med: Color::new(80, 73, 69),   // #504945
+        highlight_high: Color::new(102, 92, 84), // #665c54
+        muted: Color::new(146, 131, 116),        // #928374
+        subtle: Color::new(168, 153, 132),       // #a89984
+        text: Color::new(235, 219, 178),         // #ebdbb2
+        love: Color::new(251, 73, 52),           // #fb4934
+        gold: Color::new(250, 189, 47),          // #fabd2f
+        rose: Color::new(254, 128, 25),          // #fe8019
+        pine: Color::new(131, 165, 152),         // #83a598
+        foam: Color::new(142, 192, 124),         // #8ec07c
+        iris: Color::new(211, 134, 155),         // #d3869b
+    };
+
+    pub const NORD: Theme = Theme {
+        base: Color::new(46, 52, 64),            // #2e3440
+        surface: Color::new(59, 66, 82),         // #3b4252
+        overlay: Color::new(67, 76, 94),         // #434c5e
+        highlight_low: Color::new(59, 66, 82),   // #3b4252
+        highlight_med: Color::new(76, 86, 106),  // #4c566a
+        highlight_high: Color::new(97, 110, 136), // #616e88
+        muted: Color::new(97, 110, 136),         // #616e88
+        subtle: Color::new(216, 222, 233),       // #d8dee9
+        text: Color::new(236, 239, 244),         // #eceff4
+        love: Color::new(191, 97, 106),          // #bf616a
+        gold: Color::new(235, 203, 139),         // #ebcb8b
+        rose: Color::new(208, 135, 112),         // #d08770
+        pine: Color::new(94, 129, 172),          // #5e81ac
+        foam: Color::new(136, 192, 208),         // #88c0d0
+        iris: Color::new(180, 142, 173),         // #b48ead
+    };
+
+    /// Built-in palettes by the name used for `theme = "..."` and `--theme`
+    pub const BUILTIN: &[(&str, Theme)] = &[
+        ("rose-pine-moon", Self::ROSE_PINE_MOON),
+        ("rose-pine-dawn", Self::ROSE_PINE_DAWN),
+        ("catppuccin-mocha", Self::CATPPUCCIN_MOCHA),
+        ("gruvbox", Self::GRUVBOX),
+        ("nord", Self::NORD),
+    ];
+
+    pub fn named(name: &str) -> Option<Self> {
+        Self::BUILTIN
+            .iter()
+            .find(|(builtin, _)| builtin.eq_ignore_ascii_case(name))
+            .map(|(_, theme)| *theme)
+    }
+
+    /// Resolve a `theme` value: a built-in name, "pywal" for the colors
+    /// generated by `wal`, or "base16:<scheme.yaml>"
+    pub fn load(name: &str) -> Option<Self> {
+        if name.eq_ignore_ascii_case("pywal") {
+            Self::pywal()
+        } else if let Some(path) = name.strip_prefix("base16:") {
+            Self::base16(&expand_tilde(path))
+        } else {
+            Self::named(name)
+        }
+    }
+
+    /// The `theme` palette (Rose Pine Moon if empty, unknown or unreadable),
+    /// adjusted for `transparent`, with `[colors]` overrides applied, e.g.
+    /// `love = "#ff0000"`. Unknown roles and malformed colors are ignored.
+    pub fn from_config(config: &Config) -> Self {
+        let mut theme = Self::load(&config.theme).unwrap_or_default();
+        if config.transparent {
+            theme = theme.for_transparency();
+        }
+        for (name, value) in &config.colors {
+            if let (Some(slot), Some(color)) = (theme.slot_mut(name), Color::from_hex(value)) {
+                *slot = color;
+            }
+        }
+        theme
+    }
+
+    /// Over the terminal's own background (or wallpaper) the faint roles can't
+    /// rely on contrast with `base`, so pull them toward `text`
+    fn for_transparency(self) -> Self {
+        Self {
+            highlight_med: self.highlight_med.mix(self.text, 0.3),
+            highlight_high: self.highlight_high.mix(self.text, 0.3),
+            muted: self.muted.mix(self.text, 0.35),
+            subtle: self.subtle.mix(self.text, 0.2),
+            ..self
+        }
+    }
+
+    /// Map pywal's `~/.cache/wal/colors.json` onto the roles. Its palette has
+    /// no intermediate shades, so those are blended from background to foreground.
+    fn pywal() -> Option<Self> {
+        let path = dirs::cache_dir()?.join("wal").join("colors.json");
+        let json: serde_json::Value = serde_json::from_str(&fs::read_to_string(path).ok()?).ok()?;
+        let special = |key: &str| json["special"][key].as_str().and_then(Color::from_hex);
+        let color = |n: u8| json["colors"][format!("color{}", n)].as_str().and_then(Color::from_hex);
+        let (bg, fg) = (special("background")?, special("foreground")?);
+
+        Some(Self {
+            base: bg,
+            surface: bg.mix(fg, 0.05),
+            overlay: bg.mix(fg, 0.12),
+            highlight_low: bg.mix(fg, 0.05),
+            highlight_med: bg.mix(fg, 0.15),
+            highlight_high: bg.mix(fg, 0.25),
+            muted: color(8)?,
+            subtle: bg.mix(fg, 0.7),
+            text: fg,
+            love: color(1)?,
+            gold: color(3)?,
+            rose: color(2)?,
+            pine: color(4)?,
+            foam: color(6)?,
+            iris: color(5)?,
+        })
+    }
+
+    /// Map a base16 scheme onto the roles, following the base16 styling
+    /// guidelines. Reads `base0X: "rrggbb"` lines, flat or nested under
+    /// `palette:`, so no YAML parser is needed.
+    fn base16(path: &Path) -> Option<Self> {
+        let text = fs::read_to_string(path).ok()?;
+        let mut palette = HashMap::new();
+        for line in text.lines() {
+            let Some((key, value)) = line.trim().split_once(':') else { continue };
+            let value = value.trim();
+            let value = match value.strip_prefix('"') {
+                Some(quoted) => quoted.split('"').next().unwrap_or(""),
+                None => value.split_whitespace().next().unwrap_or(""),
+            };
+            if let Some(color) = Color::from_hex(value) {
+                palette.insert(key.trim().to_ascii_lowercase(), color);
+            }
+        }
+        let base = |n: &str| palette.get(&format!("base0{}", n)).copied();
+
+        Some(Self {
+            base: base("0")?,
+            surface: base("1")?,
+            overlay: base("2")?,
+            highlight_low: base("1")?,
+            highlight_med: base("2")?,
+            highlight_high: base("3")?,
+            muted: base("3")?,
+            subtle: base("4")?,
+            text: base("5")?,
+            love: base("8")?,
+            gold: base("a")?,
+            rose: base("9")?,
+            pine: base("d")?,
+            foam: base("c")?,
+            iris: base("e")?,
+        })
+    }
+
+    fn slot_mut(&mut self, name: &str) -> Option<&mut Color> {
+        Some(match name {
+            "base" => &mut self.base,
+            "surface" => &mut self.surface,
+            "overlay" => &mut self.overlay,
+            "highlight_low" => &mut self.highlight_low,
+            "highlight_med" => &mut self.highlight_med,
+            "highlight_high" => &mut self.highlight_high,
+            "muted" => &mut self.muted,
+            "subtle" => &mut self.subtle,
+            "text" => &mut self.text,
+            "love" => &mut self.love,
+            "gold" => &mut self.gold,
+            "rose" => &mut self.rose,
+            "pine" => &mut self.pine,
+            "foam" => &mut self.foam,
+            "iris" => &mut self.iris,
+            _ => return None,
+        })
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::ROSE_PINE_MOON
+    }
 }
 
 // Style builder for easy styling
@@ -118,7 +376,6 @@ impl Style {
         self
     }
 
-    #[allow(dead_code)]
     pub fn italic(mut self) -> Self {
         self.italic = true;
         self
@@ -2,54 +2,112 @@
 // UI Renderer - Optimized with Cached DB Lookups
 // ============================================================================
 
-use crate::app::{App, InputMode, Mode};
+use crate::app::{App, FileAction, InputMode, Mode};
+use crate::config::Config;
+use crate::icons::app_icon;
+use crate::keymap::{Action, Keymap};
+use crate::system::{split_category_filters, split_launch_args, QueryMatcher};
 use crate::terminal::Terminal;
 use crate::theme::*;
 use std::io;
 use std::collections::HashMap;
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
 
 pub struct Ui {
     term: Terminal,
     width: u16,
     height: u16,
+    // Width left to the results when the preview pane takes the right half
+    list_width: u16,
     // Cache TUI status to avoid DB queries during rendering
     tui_cache: HashMap<String, bool>,
+    theme: Theme,
+    // Only read, to show the configured keys in the status bar hints
+    keymap: Keymap,
 }
 
+/// (icon, text, aux text, is selected, accent icon, matched char indices of text)
+type ResultItem = (String, String, String, bool, bool, Vec<usize>);
+
 const COL_CONTENT_START: u16 = 2;
 const ROW_INPUT: u16 = 1;
+const ROW_BANNER: u16 = 2;
 const ROW_RESULTS_START: u16 = 3;
+/// Narrower terminals keep the full width for results
+const MIN_PREVIEW_WIDTH: u16 = 60;
+/// Results laid out per frame, ending at the selection
+const MAX_PREPARED: u16 = 50;
 
 impl Ui {
-    pub fn new() -> io::Result<Self> {
-        let term = Terminal::new()?;
+    pub fn new(config: &Config) -> io::Result<Self> {
+        let theme = Theme::from_config(config);
+        let term = Terminal::new(theme, config.transparent)?;
         let (width, height) = term.size();
         Ok(Self { 
             term, 
             width, 
             height,
+            list_width: width,
             tui_cache: HashMap::new(),
+            theme,
+            keymap: Keymap::from_config(&config.keys),
         })
     }
 
+    /// A Ui drawing into an in-memory terminal, for tests
+    #[cfg(test)]
+    pub fn headless(config: &Config, width: u16, height: u16) -> Self {
+        let theme = Theme::from_config(config);
+        Self {
+            term: Terminal::headless(theme, width, height),
+            width,
+            height,
+            list_width: width,
+            tui_cache: HashMap::new(),
+            theme,
+            keymap: Keymap::from_config(&config.keys),
+        }
+    }
+
+    /// The terminal the frames were drawn into
+    #[cfg(test)]
+    pub fn terminal(&self) -> &Terminal {
+        &self.term
+    }
+
+    /// Lay out for a resized terminal; the caller renders again
+    pub fn resize(&mut self, width: u16, height: u16) {
+        self.term.resize(width, height);
+        self.width = width;
+        self.height = height;
+    }
+
     pub fn render(&mut self, app: &App) -> io::Result<()> {
         // Refresh TUI cache before rendering
         self.refresh_tui_cache(app);
+        self.list_width = if app.preview_visible() && self.width >= MIN_PREVIEW_WIDTH {
+            self.width / 2
+        } else {
+            self.width
+        };
 
-        self.term.clear()?;
+        self.term.clear();
         self.draw_border()?;
+        self.draw_status_bar(app)?;
         self.draw_input_row(app)?;
+        self.draw_banner(app)?;
         self.draw_results(app)?;
+        self.draw_preview(app)?;
+        self.draw_file_menu(app)?;
+        self.draw_app_details(app)?;
 
-        if app.input_mode == InputMode::Insert {
-            let cursor_x = self.calculate_cursor_x(app);
-            self.term.write(crate::theme::SHOW_CURSOR)?;
-            self.term.move_to(cursor_x, ROW_INPUT)?;
-            self.term.write(RESET)?;
-        } else {
-            self.term.write(crate::theme::HIDE_CURSOR)?;
-        }
+        // A bar while typing, a block on the character normal mode edits
+        let cursor_x = self.calculate_cursor_x(app);
+        let shape = if app.input_mode == InputMode::Insert { CURSOR_BAR } else { CURSOR_BLOCK };
+        self.term.place_cursor(cursor_x, ROW_INPUT, shape);
 
+        // Only the cells that changed since the last frame reach the terminal
         self.term.flush()?;
         Ok(())
     }
@@ -60,11 +118,7 @@ impl Ui {
         
         if app.mode == Mode::Apps {
             for app_entry in &app.filtered_apps {
-                let is_tui = if app.db.has_entry(&app_entry.name) {
-                    app.db.is_tui_app(&app_entry.name)
-                } else {
-                    app_entry.is_cli_only
-                };
+                let is_tui = app.is_tui(app_entry);
                 self.tui_cache.insert(app_entry.name.clone(), is_tui);
             }
         }
@@ -81,7 +135,7 @@ impl Ui {
     fn draw_border(&mut self) -> io::Result<()> {
         let w = self.width;
         let h = self.height;
-        let color = RosePineMoon::HIGHLIGHT_MED;
+        let color = self.theme.highlight_med;
 
         self.term.write_styled(0, 0, "╭", &Style::new().fg(color))?;
         self.term.write_styled(w - 1, 0, "╮", &Style::new().fg(color))?;
@@ -106,31 +160,149 @@ impl Ui {
 
     fn draw_input_row(&mut self, app: &App) -> io::Result<()> {
         let mut x = COL_CONTENT_START;
-        let (prompt_text, prompt_color) = match app.mode {
-            Mode::Apps => ("Apps > ", RosePineMoon::LOVE),
-            Mode::Files => ("Files > ", RosePineMoon::GOLD),
-        };
+        let (prompt_text, prompt_color) = self.prompt(app);
 
-        self.term.write_at(x, ROW_INPUT, &Style::new().fg(prompt_color).bold().apply(prompt_text))?;
-        x += prompt_text.len() as u16;
+        self.term.write_at(x, ROW_INPUT, &Style::new().fg(prompt_color).bold().apply(&prompt_text))?;
+        x += display_width(&prompt_text) as u16;
 
         let input_style = if app.input_mode == InputMode::Insert {
-            Style::new().fg(RosePineMoon::TEXT)
+            Style::new().fg(self.theme.text)
         } else {
-            Style::new().fg(RosePineMoon::SUBTLE)
+            Style::new().fg(self.theme.subtle)
         };
         self.term.write_at(x, ROW_INPUT, &input_style.apply(&app.input))?;
+
+        if app.private {
+            let label = "\u{f070} private";
+            let label_x = self.width.saturating_sub(display_width(label) as u16 + COL_CONTENT_START);
+            self.term.write_at(label_x, ROW_INPUT, &Style::new().fg(self.theme.iris).apply(label))?;
+        }
         Ok(())
     }
 
-    fn calculate_cursor_x(&self, app: &App) -> u16 {
+    /// Launch errors, or else a passing confirmation, between input and results
+    fn draw_banner(&mut self, app: &App) -> io::Result<()> {
+        let (text, color) = if let Some(error) = &app.launch_error {
+            (format!("\u{f071} {}", error), self.theme.love)
+        } else if let Some((message, _)) = &app.flash {
+            (format!("\u{f00c} {}", message), self.theme.foam)
+        } else {
+            return Ok(());
+        };
+        let max = self.width.saturating_sub(COL_CONTENT_START * 2) as usize;
+        let banner = truncate(&text, max);
+        self.term.write_at(COL_CONTENT_START, ROW_BANNER, &Style::new().fg(color).apply(banner))
+    }
+
+    fn prompt(&self, app: &App) -> (String, Color) {
+        if let Some(open_with) = &app.open_with {
+            let name = std::path::Path::new(&open_with.path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_default();
+            return (format!("Open {} with > ", name), self.theme.gold);
+        }
+        let (name, color) = self.mode_label(app);
+        if app.mode == Mode::Browse {
+            let dir = app.browse_dir.to_string_lossy().to_string();
+            let dir = match dirs::home_dir() {
+                Some(home) if dir.starts_with(&*home.to_string_lossy()) => {
+                    dir.replacen(&*home.to_string_lossy(), "~", 1)
+                }
+                _ => dir,
+            };
+            return (format!("{} > ", dir), color);
+        }
+        (format!("{} > ", name), color)
+    }
+
+    /// Name and accent color of the current mode
+    fn mode_label(&self, app: &App) -> (String, Color) {
+        match app.mode {
+            Mode::Apps => ("Apps".to_string(), self.theme.love),
+            Mode::Files => ("Files".to_string(), self.theme.gold),
+            Mode::Grep => ("Grep".to_string(), self.theme.pine),
+            Mode::Browse => ("Browse".to_string(), self.theme.foam),
+            Mode::Zoxide => ("Jump".to_string(), self.theme.iris),
+            Mode::Projects => ("Projects".to_string(), self.theme.rose),
+            Mode::History => ("History".to_string(), self.theme.subtle),
+            Mode::Plugin(i) => (app.config.plugins[i].name.clone(), self.theme.foam),
+        }
+    }
+
+    // ========================================================================
+    // Status Bar
+    // ========================================================================
+
+    /// Mode, insert/normal, match counts and key hints, set into the bottom border.
+    /// Hints, then counts, are dropped when the terminal is too narrow.
+    fn draw_status_bar(&mut self, app: &App) -> io::Result<()> {
+        let y = self.height.saturating_sub(1);
+        let (mode, mode_color) = self.mode_label(app);
+        let (input_mode, input_color) = match app.input_mode {
+            InputMode::Insert => ("INSERT", self.theme.pine),
+            InputMode::Normal => ("NORMAL", self.theme.iris),
+        };
+
+        let mut counts = match app.candidate_count() {
+            Some(total) => format!("{}/{} matches", app.result_count(), total),
+            None => format!("{} matches", app.result_count()),
+        };
+        if app.mode == Mode::Apps && app.hidden_dormant > 0 {
+            counts.push_str(&format!(" · {} dormant hidden", app.hidden_dormant));
+        }
+        let hints = self.hints(app).join("  ");
+
+        let mode = format!(" {} ", mode);
+        let input_mode = format!(" {} ", input_mode);
+        let counts = format!(" {} ", counts);
+        let hints = format!(" {} ", hints);
+        let room = self.width.saturating_sub(COL_CONTENT_START * 2) as usize;
+        let left = display_width(&mode) + display_width(&input_mode);
+        let show_counts = left + display_width(&counts) <= room;
+        let show_hints = show_counts && left + display_width(&counts) + display_width(&hints) < room;
+        if left > room {
+            return Ok(());
+        }
+
         let mut x = COL_CONTENT_START;
-        let prompt_len = match app.mode {
-            Mode::Apps => 7,
-            Mode::Files => 8,
+        self.term.write_at(x, y, &Style::new().fg(mode_color).bold().apply(&mode))?;
+        x += display_width(&mode) as u16;
+        self.term.write_at(x, y, &Style::new().fg(input_color).apply(&input_mode))?;
+        x += display_width(&input_mode) as u16;
+        if show_counts {
+            self.term.write_at(x + 1, y, &Style::new().fg(self.theme.subtle).apply(&counts))?;
+        }
+        if show_hints {
+            let hints_x = self.width - COL_CONTENT_START - display_width(&hints) as u16;
+            self.term.write_at(hints_x, y, &Style::new().fg(self.theme.muted).apply(&hints))?;
+        }
+        Ok(())
+    }
+
+    /// A few keys worth knowing right now, as the keymap binds them
+    fn hints(&self, app: &App) -> Vec<String> {
+        let open = if app.mode == Mode::Apps { "launch" } else { "open" };
+        let wanted = match (app.input_mode, app.mode) {
+            (InputMode::Insert, _) => [(Action::Launch, open), (Action::ToggleMode, "mode"), (Action::NormalMode, "normal")],
+            (InputMode::Normal, Mode::Apps) => [(Action::Launch, open), (Action::Details, "details"), (Action::Quit, "quit")],
+            (InputMode::Normal, Mode::Files | Mode::Grep | Mode::Browse) => {
+                [(Action::Launch, open), (Action::FileMenu, "actions"), (Action::Quit, "quit")]
+            }
+            (InputMode::Normal, _) => [(Action::Launch, open), (Action::InsertMode, "search"), (Action::Quit, "quit")],
         };
-        x += prompt_len;
-        x += app.cursor_pos as u16;
+        wanted
+            .into_iter()
+            .filter_map(|(action, label)| {
+                self.keymap.key_for(app.input_mode, action).map(|key| format!("{} {}", key, label))
+            })
+            .collect()
+    }
+
+    fn calculate_cursor_x(&self, app: &App) -> u16 {
+        let mut x = COL_CONTENT_START;
+        x += display_width(&self.prompt(app).0) as u16;
+        x += display_width(app.input_before_cursor()) as u16;
         x
     }
 
@@ -143,8 +315,13 @@ impl Ui {
         let list_height = max_render_row.saturating_sub(ROW_RESULTS_START);
 
         let all_items = match app.mode {
-            Mode::Apps => self.prepare_app_items(app, 50),
-            Mode::Files => self.prepare_file_items(app, 50),
+            Mode::Apps => self.prepare_app_items(app, MAX_PREPARED),
+            Mode::Files => self.prepare_file_items(app, MAX_PREPARED),
+            Mode::Grep => self.prepare_grep_items(app, MAX_PREPARED),
+            Mode::Browse => self.prepare_browse_items(app, MAX_PREPARED),
+            Mode::Zoxide | Mode::Projects => self.prepare_dir_items(app, MAX_PREPARED),
+            Mode::History => self.prepare_history_items(app, MAX_PREPARED),
+            Mode::Plugin(_) => self.prepare_plugin_items(app, MAX_PREPARED),
         };
 
         // Calculate optimal start_index for scrolling
@@ -152,7 +329,7 @@ impl Ui {
         let mut current_view_height = 0;
 
         for i in (0..=app.selected_index).rev() {
-            if let Some((icon, text, aux_text, _, _)) = all_items.get(i) {
+            if let Some((icon, text, aux_text, _, _, _)) = all_items.get(i) {
                 let item_height = self.measure_item_height(icon, text, aux_text);
                 
                 if current_view_height + item_height > list_height {
@@ -165,16 +342,22 @@ impl Ui {
 
         // Render visible items
         let mut current_row = ROW_RESULTS_START;
+        let mut shown = 0;
 
-        for (_idx, (icon, text, aux_text, is_selected, is_tui)) in all_items.iter().enumerate().skip(start_index) {
+        for (idx, (icon, text, aux_text, is_selected, is_tui, matched)) in all_items.iter().enumerate().skip(start_index) {
             if current_row >= max_render_row {
                 break;
             }
+            shown += 1;
+
+            // Mark in the gutter between border and indicator
+            let mark = if app.is_marked(idx) { "\u{258c}" } else { " " };
+            self.term.write_at(COL_CONTENT_START - 1, current_row, &Style::new().fg(self.theme.gold).apply(mark))?;
 
             // Selection indicator
             let indicator = if *is_selected { "> " } else { "  " };
             let ind_style = if *is_selected { 
-                Style::new().fg(RosePineMoon::LOVE).bold() 
+                Style::new().fg(self.theme.love).bold() 
             } else { 
                 Style::new() 
             };
@@ -183,41 +366,45 @@ impl Ui {
             // Icon
             let mut x = COL_CONTENT_START + 2;
             if !icon.is_empty() {
-                let icon_color = if *is_tui { RosePineMoon::PINE } else { RosePineMoon::SUBTLE };
+                let icon_color = if *is_tui { self.theme.pine } else { self.theme.subtle };
                 self.term.write_at(x, current_row, &Style::new().fg(icon_color).apply(icon))?;
-                x += icon.chars().count() as u16 + 1;
+                x += display_width(icon) as u16 + 1;
             }
 
             // Main text
             let name_style = if *is_selected {
-                Style::new().fg(RosePineMoon::TEXT).bold()
+                Style::new().fg(self.theme.text).bold()
             } else {
-                Style::new().fg(RosePineMoon::SUBTLE)
+                Style::new().fg(self.theme.subtle)
             };
-            self.term.write_at(x, current_row, &name_style.apply(text))?;
-            x += text.chars().count() as u16 + 1;
+            let match_style = name_style.fg(self.theme.rose);
+            self.term.write_at(x, current_row, &highlight(text, matched, name_style, match_style))?;
+            x += display_width(text) as u16 + 1;
 
             // Path with smart wrapping
             if !aux_text.is_empty() {
-                let available_width = (self.width.saturating_sub(x).saturating_sub(1)) as usize;
+                let available_width = (self.list_width.saturating_sub(x).saturating_sub(1)) as usize;
                 
-                if aux_text.len() <= available_width {
-                    let path_style = Style::new().fg(RosePineMoon::MUTED);
+                if display_width(aux_text) <= available_width {
+                    let path_style = Style::new().fg(self.theme.muted);
                     self.term.write_at(x, current_row, &path_style.apply(aux_text))?;
                     current_row += 1; 
                 } else {
-                    let split_idx = aux_text[..available_width].rfind('/').unwrap_or(available_width);
+                    // Snippets and paths may hold wide characters - cut by columns,
+                    // never inside a grapheme
+                    let limit = truncate(aux_text, available_width).len();
+                    let split_idx = aux_text[..limit].rfind('/').unwrap_or(limit);
                     
                     let part1 = &aux_text[..split_idx];
-                    let path_style = Style::new().fg(RosePineMoon::MUTED);
+                    let path_style = Style::new().fg(self.theme.muted);
                     self.term.write_at(x, current_row, &path_style.apply(part1))?;
                     current_row += 1;
 
                     if current_row < max_render_row {
                         let part2 = &aux_text[split_idx..];
-                        let avail_2 = (self.width.saturating_sub(x).saturating_sub(1)) as usize;
-                        let part2_display = if part2.len() > avail_2 {
-                            format!("{}...", &part2[..avail_2.saturating_sub(3)])
+                        let avail_2 = (self.list_width.saturating_sub(x).saturating_sub(1)) as usize;
+                        let part2_display = if display_width(part2) > avail_2 {
+                            format!("{}...", truncate(part2, avail_2.saturating_sub(3)))
                         } else {
                             part2.to_string()
                         };
@@ -237,6 +424,200 @@ impl Ui {
              current_row += 1;
         }
 
+        // Cold start: nothing cached yet, the background scan is still running
+        if all_items.is_empty() && app.mode == Mode::Apps && app.is_scanning() {
+            let style = Style::new().fg(self.theme.muted).italic();
+            self.term.write_at(COL_CONTENT_START + 2, ROW_RESULTS_START, &style.apply("Scanning applications…"))?;
+        }
+
+        // The prepared items start this far into the full result list
+        let offset = (app.selected_index + 1).saturating_sub(MAX_PREPARED as usize);
+        self.draw_scrollbar(offset + start_index, shown, app.result_count())
+    }
+
+    /// A thumb on the results' right edge showing which part of the list is
+    /// on screen, when it does not all fit
+    fn draw_scrollbar(&mut self, first: usize, shown: usize, total: usize) -> io::Result<()> {
+        let track = self.height.saturating_sub(1).saturating_sub(ROW_RESULTS_START) as usize;
+        if shown >= total || track == 0 {
+            return Ok(());
+        }
+
+        let size = (track * shown / total).clamp(1, track);
+        // Only the first page puts the thumb at the top, and only the last at the bottom
+        let start = if first + shown >= total {
+            track - size
+        } else if first > 0 {
+            (track * first / total).clamp(1, track - size)
+        } else {
+            0
+        };
+
+        let x = self.list_width.saturating_sub(1);
+        let style = Style::new().fg(self.theme.muted);
+        for row in start..start + size {
+            self.term.write_at(x, ROW_RESULTS_START + row as u16, &style.apply("┃"))?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // Preview Pane (right half in Files mode)
+    // ========================================================================
+
+    fn draw_preview(&mut self, app: &App) -> io::Result<()> {
+        if !app.preview_visible() || self.list_width == self.width {
+            return Ok(());
+        }
+
+        let sep = self.list_width;
+        let pane = self.width.saturating_sub(sep + 3) as usize;
+        let ext = app
+            .preview_path
+            .as_deref()
+            .and_then(|p| std::path::Path::new(p).extension())
+            .map(|e| e.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        let syntax = syntax_for(&ext);
+        let border = Style::new().fg(self.theme.highlight_med);
+
+        for (i, y) in (ROW_RESULTS_START..self.height.saturating_sub(1)).enumerate() {
+            // Covers any result text that ran past the split
+            let line = app.preview.get(i).map(|l| truncate(l, pane)).unwrap_or_default();
+            let pad = " ".repeat(pane - display_width(line));
+            let text = match &syntax {
+                Some(syntax) => highlight_code(line, syntax, &self.theme),
+                None => Style::new().fg(self.theme.subtle).apply(line),
+            };
+            self.term.write_at(sep, y, &format!("{} {}{} ", border.apply("│"), text, pad))?;
+        }
+        Ok(())
+    }
+
+    // ========================================================================
+    // App Details Panel (drawn over the results)
+    // ========================================================================
+
+    fn draw_app_details(&mut self, app: &App) -> io::Result<()> {
+        let Some(details) = &app.app_details else {
+            return Ok(());
+        };
+
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let last_used = if details.last_used == 0 {
+            "never".to_string()
+        } else {
+            format!("{} ({})", format_date(details.last_used), format_age(now.saturating_sub(details.last_used)))
+        };
+        let terminal = match (details.is_tui, details.tui_stored) {
+            (true, true) => "yes (stored)",
+            (true, false) => "yes (default)",
+            (false, true) => "no (stored)",
+            (false, false) => "no (default)",
+        };
+        let rows = [
+            ("Exec", details.exec.clone()),
+            ("Source", if details.desktop_path.is_empty() { "$PATH or config".to_string() } else { details.desktop_path.clone() }),
+            ("Categories", if details.categories.is_empty() { "-".to_string() } else { details.categories.join(", ") }),
+            ("Launches", details.usage.to_string()),
+            ("Last used", last_used),
+            ("Terminal", terminal.to_string()),
+            ("Score", format!("{} (seed {} + frecency {})", details.total_score, details.seed_score, details.frecency)),
+            ("Dormant", if details.is_dormant { "yes" } else { "no" }.to_string()),
+        ];
+
+        let x = COL_CONTENT_START + 2;
+        let width = self.width.saturating_sub(x + 3);
+        if width < 30 || self.height < ROW_RESULTS_START + rows.len() as u16 + 3 {
+            return Ok(());
+        }
+        let inner = (width - 2) as usize;
+        let label_width = 12;
+        let value_width = inner.saturating_sub(label_width + 2);
+        let border = Style::new().fg(self.theme.highlight_med);
+
+        let title = truncate(&format!(" {} ", details.name), inner.saturating_sub(2)).to_string();
+        let fill = inner.saturating_sub(display_width(&title) + 1);
+        let mut y = ROW_RESULTS_START;
+        self.term.write_at(x, y, &border.apply("╭─"))?;
+        self.term.write_at(x + 2, y, &Style::new().fg(self.theme.foam).bold().apply(&title))?;
+        self.term.write_at(x + 2 + display_width(&title) as u16, y, &border.apply(&format!("{}╮", "─".repeat(fill))))?;
+
+        for (label, value) in rows {
+            y += 1;
+            let value: String = if display_width(&value) > value_width {
+                format!("{}…", truncate(&value, value_width.saturating_sub(1)))
+            } else {
+                value
+            };
+            let pad = " ".repeat(value_width - display_width(&value));
+
+            self.term.write_at(x, y, &border.apply("│"))?;
+            self.term.write_at(x + 1, y, &Style::new().fg(self.theme.subtle).apply(&format!(" {:<1$}", label, label_width)))?;
+            self.term.write_at(x + 2 + label_width as u16, y, &Style::new().fg(self.theme.text).apply(&format!("{}{} ", value, pad)))?;
+            self.term.write_at(x + width - 1, y, &border.apply("│"))?;
+        }
+
+        y += 1;
+        self.term.write_at(x, y, &border.apply(&format!("╰{}╯", "─".repeat(inner))))?;
+        Ok(())
+    }
+
+    // ========================================================================
+    // File Action Menu (drawn over the results)
+    // ========================================================================
+
+    fn draw_file_menu(&mut self, app: &App) -> io::Result<()> {
+        let Some(menu) = &app.file_menu else {
+            return Ok(());
+        };
+
+        let x = COL_CONTENT_START + 2;
+        let label_width = FileAction::ALL.iter().map(|a| display_width(a.label())).max().unwrap_or(0);
+        // "│ > k  label │"
+        let width = ((label_width + 10) as u16).min(self.width.saturating_sub(x + 2));
+        if width < 12 || self.height < ROW_RESULTS_START + FileAction::ALL.len() as u16 + 3 {
+            return Ok(());
+        }
+        let inner = (width - 2) as usize;
+        let border = Style::new().fg(self.theme.highlight_med);
+
+        // Title is the file name, cut to fit between the corners
+        let name = std::path::Path::new(&menu.path)
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let title = truncate(&format!(" {} ", name), inner.saturating_sub(2)).to_string();
+        let fill = inner.saturating_sub(display_width(&title) + 1);
+        let mut y = ROW_RESULTS_START;
+        self.term.write_at(x, y, &border.apply("╭─"))?;
+        self.term.write_at(x + 2, y, &Style::new().fg(self.theme.gold).bold().apply(&title))?;
+        self.term.write_at(x + 2 + display_width(&title) as u16, y, &border.apply(&format!("{}╮", "─".repeat(fill))))?;
+
+        for (i, action) in FileAction::ALL.iter().enumerate() {
+            y += 1;
+            let is_selected = i == menu.selected;
+            let indicator = if is_selected { "> " } else { "  " };
+            let label_style = if is_selected {
+                Style::new().fg(self.theme.text).bold()
+            } else {
+                Style::new().fg(self.theme.subtle)
+            };
+            let label = truncate(action.label(), inner.saturating_sub(6));
+            let pad = " ".repeat(inner.saturating_sub(display_width(label) + 6));
+
+            self.term.write_at(x, y, &border.apply("│"))?;
+            self.term.write_at(x + 1, y, &Style::new().fg(self.theme.love).bold().apply(&format!(" {}", indicator)))?;
+            self.term.write_at(x + 4, y, &Style::new().fg(self.theme.foam).apply(&format!("{}  ", action.key())))?;
+            self.term.write_at(x + 7, y, &label_style.apply(&format!("{}{}", label, pad)))?;
+            self.term.write_at(x + width - 1, y, &border.apply("│"))?;
+        }
+
+        y += 1;
+        self.term.write_at(x, y, &border.apply(&format!("╰{}╯", "─".repeat(inner))))?;
         Ok(())
     }
 
@@ -247,26 +628,31 @@ impl Ui {
 
         let mut x = COL_CONTENT_START + 2;
         if !icon.is_empty() {
-            x += icon.chars().count() as u16 + 1;
+            x += display_width(icon) as u16 + 1;
         }
-        x += text.chars().count() as u16 + 1;
+        x += display_width(text) as u16 + 1;
 
-        let available_width = (self.width.saturating_sub(x).saturating_sub(1)) as usize;
+        let available_width = (self.list_width.saturating_sub(x).saturating_sub(1)) as usize;
         
-        if aux_text.len() <= available_width {
+        if display_width(aux_text) <= available_width {
             1
         } else {
             2
         }
     }
 
-    fn prepare_app_items(&self, app: &App, max: u16) -> Vec<(String, String, String, bool, bool)> {
+    fn prepare_app_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
         let start_index = if app.selected_index >= max as usize {
             app.selected_index - (max as usize) + 1
         } else {
             0
         };
 
+        // Category filters and "--" arguments never match text, so only the
+        // remaining query is highlighted
+        let (query, _) = split_launch_args(&app.input);
+        let matcher = QueryMatcher::parse(&split_category_filters(&query).0);
+
         app.filtered_apps
             .iter()
             .enumerate()
@@ -275,19 +661,22 @@ impl Ui {
             .map(|(i, entry)| {
                 let is_selected = i == app.selected_index;
                 let is_tui = self.get_tui_status(&entry.name);
-                let icon = if is_tui { "\u{e795}" } else { "" };
-                (icon.to_string(), entry.name.clone(), "".to_string(), is_selected, is_tui)
+                let icon = app_icon(entry, is_tui, &app.config.icons);
+                let matched = matcher.indices(&entry.name).unwrap_or_default();
+                (icon.to_string(), entry.name.clone(), "".to_string(), is_selected, is_tui, matched)
             })
             .collect()
     }
 
-    fn prepare_file_items(&self, app: &App, max: u16) -> Vec<(String, String, String, bool, bool)> {
+    fn prepare_file_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
         let start_index = if app.selected_index >= max as usize {
             app.selected_index - (max as usize) + 1
         } else {
             0
         };
 
+        let matcher = QueryMatcher::parse(&app.input);
+
         app.filtered_files
             .iter()
             .enumerate()
@@ -305,8 +694,311 @@ impl Ui {
                     .map(|p| p.to_string_lossy().to_string())
                     .unwrap_or_default();
 
-                ("".to_string(), name, parent, is_selected, false)
+                // Files match on the full path; only the file name part is highlighted
+                let matched = name_indices(&matcher, path_str, &name);
+
+                ("".to_string(), name, parent, is_selected, false, matched)
+            })
+            .collect()
+    }
+
+    fn prepare_grep_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
+        let start_index = if app.selected_index >= max as usize {
+            app.selected_index - (max as usize) + 1
+        } else {
+            0
+        };
+
+        app.filtered_matches
+            .iter()
+            .enumerate()
+            .skip(start_index)
+            .take(max as usize)
+            .map(|(i, hit)| {
+                let is_selected = i == app.selected_index;
+                let name = std::path::Path::new(&hit.path)
+                    .file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| hit.path.clone());
+
+                ("".to_string(), format!("{}:{}", name, hit.line), hit.text.clone(), is_selected, false, Vec::new())
+            })
+            .collect()
+    }
+
+    fn prepare_history_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
+        let start_index = if app.selected_index >= max as usize {
+            app.selected_index - (max as usize) + 1
+        } else {
+            0
+        };
+
+        let matcher = QueryMatcher::parse(&app.input);
+        let now = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        app.filtered_history
+            .iter()
+            .enumerate()
+            .skip(start_index)
+            .take(max as usize)
+            .map(|(i, entry)| {
+                let is_selected = i == app.selected_index;
+                let line = entry.command_line();
+                let matched = matcher.indices(&line).unwrap_or_default();
+                let age = format_age(now.saturating_sub(entry.launched_at));
+                ("\u{f1da}".to_string(), line, age, is_selected, entry.is_tui, matched)
+            })
+            .collect()
+    }
+
+    fn prepare_plugin_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
+        let start_index = if app.selected_index >= max as usize {
+            app.selected_index - (max as usize) + 1
+        } else {
+            0
+        };
+
+        app.plugin_items
+            .iter()
+            .enumerate()
+            .skip(start_index)
+            .take(max as usize)
+            .map(|(i, item)| {
+                let is_selected = i == app.selected_index;
+                (item.icon.clone(), item.title.clone(), item.subtitle.clone(), is_selected, false, Vec::new())
+            })
+            .collect()
+    }
+
+    fn prepare_browse_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
+        let start_index = if app.selected_index >= max as usize {
+            app.selected_index - (max as usize) + 1
+        } else {
+            0
+        };
+
+        let matcher = QueryMatcher::parse(&app.input);
+
+        app.filtered_entries
+            .iter()
+            .enumerate()
+            .skip(start_index)
+            .take(max as usize)
+            .map(|(i, entry)| {
+                let is_selected = i == app.selected_index;
+                // Directories get a folder glyph and the accent icon color
+                let (icon, name) = if entry.is_dir {
+                    ("\u{f07b}", format!("{}/", entry.name))
+                } else {
+                    ("\u{f15b}", entry.name.clone())
+                };
+                let matched = matcher.indices(&entry.name).unwrap_or_default();
+                (icon.to_string(), name, "".to_string(), is_selected, entry.is_dir, matched)
+            })
+            .collect()
+    }
+
+    fn prepare_dir_items(&self, app: &App, max: u16) -> Vec<ResultItem> {
+        let start_index = if app.selected_index >= max as usize {
+            app.selected_index - (max as usize) + 1
+        } else {
+            0
+        };
+
+        let matcher = QueryMatcher::parse(&app.input);
+
+        app.filtered_dirs
+            .iter()
+            .enumerate()
+            .skip(start_index)
+            .take(max as usize)
+            .map(|(i, dir)| {
+                let is_selected = i == app.selected_index;
+                let path = std::path::Path::new(dir);
+
+                let name = path.file_name()
+                    .map(|s| s.to_string_lossy().to_string())
+                    .unwrap_or_else(|| dir.clone());
+
+                let parent = path.parent()
+                    .map(|p| p.to_string_lossy().to_string())
+                    .unwrap_or_default();
+
+                let matched = name_indices(&matcher, dir, &name);
+
+                ("\u{f07b}".to_string(), name, parent, is_selected, true, matched)
             })
             .collect()
     }
 }
+
+/// Columns `text` takes on screen: wide CJK and emoji count twice, combining marks not at all
+fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// The longest start of `text` that fits in `max` columns, cut between graphemes
+fn truncate(text: &str, max: usize) -> &str {
+    let mut width = 0;
+    for (i, grapheme) in text.grapheme_indices(true) {
+        width += grapheme.width();
+        if width > max {
+            return &text[..i];
+        }
+    }
+    text
+}
+
+/// Render `text` with the chars at `matched` in the accent style
+fn highlight(text: &str, matched: &[usize], base: Style, accent: Style) -> String {
+    if matched.is_empty() {
+        return base.apply(text);
+    }
+
+    // Group consecutive chars with the same style into one styled run
+    let mut out = String::new();
+    let mut run = String::new();
+    let mut run_matched = false;
+    for (i, c) in text.chars().enumerate() {
+        let is_match = matched.contains(&i);
+        if is_match != run_matched && !run.is_empty() {
+            out.push_str(&if run_matched { accent } else { base }.apply(&run));
+            run.clear();
+        }
+        run_matched = is_match;
+        run.push(c);
+    }
+    out.push_str(&if run_matched { accent } else { base }.apply(&run));
+    out
+}
+
+/// Just enough of a language to color a preview: its line comment marker
+struct Syntax {
+    comment: &'static str,
+    /// Whether '...' is a string; Rust lifetimes would color the rest of the line
+    single_quotes: bool,
+}
+
+/// Recognised source extensions; anything else previews as plain text
+fn syntax_for(ext: &str) -> Option<Syntax> {
+    let comment = match ext {
+        "rs" | "c" | "h" | "cpp" | "hpp" | "cc" | "js" | "jsx" | "ts" | "tsx" | "go" | "java" | "kt" | "swift"
+        | "zig" | "cs" | "scss" | "dart" => "//",
+        "py" | "sh" | "bash" | "zsh" | "fish" | "rb" | "pl" | "toml" | "yaml" | "yml" | "conf" | "nix" | "r" => "#",
+        "lua" | "sql" | "hs" => "--",
+        "json" | "css" => "",
+        _ => return None,
+    };
+    Some(Syntax { comment, single_quotes: ext != "rs" })
+}
+
+const KEYWORDS: &[&str] = &[
+    "as", "async", "await", "break", "case", "class", "const", "continue", "def", "do", "elif", "else", "enum",
+    "export", "false", "fn", "for", "from", "func", "function", "if", "impl", "import", "in", "let", "local",
+    "match", "mod", "mut", "nil", "None", "null", "pub", "return", "self", "Self", "static", "struct", "then",
+    "trait", "true", "True", "False", "type", "use", "var", "where", "while",
+];
+
+/// Color one preview line: comments, string literals, numbers and common keywords
+fn highlight_code(line: &str, syntax: &Syntax, theme: &Theme) -> String {
+    let plain = Style::new().fg(theme.text);
+    let chars: Vec<char> = line.chars().collect();
+    let comment: Vec<char> = syntax.comment.chars().collect();
+    let mut out = String::new();
+    // Neighbouring tokens of the same style share one escape sequence
+    let mut run = String::new();
+    let mut run_style = plain;
+    let mut i = 0;
+
+    while i < chars.len() {
+        let c = chars[i];
+
+        if !comment.is_empty() && chars[i..].starts_with(&comment) {
+            out.push_str(&run_style.apply(&run));
+            run.clear();
+            let rest: String = chars[i..].iter().collect();
+            out.push_str(&Style::new().fg(theme.muted).italic().apply(&rest));
+            break;
+        }
+
+        let start = i;
+        let style = if c == '"' || (c == '\'' && syntax.single_quotes) {
+            // Up to the closing quote, skipping escaped ones
+            i += 1;
+            while i < chars.len() && chars[i] != c {
+                i += if chars[i] == '\\' { 2 } else { 1 };
+            }
+            i = (i + 1).min(chars.len());
+            Style::new().fg(theme.gold)
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.' || chars[i] == '_') {
+                i += 1;
+            }
+            Style::new().fg(theme.iris)
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_') {
+                i += 1;
+            }
+            let word: String = chars[start..i].iter().collect();
+            if KEYWORDS.contains(&word.as_str()) {
+                Style::new().fg(theme.pine).bold()
+            } else {
+                plain
+            }
+        } else {
+            i += 1;
+            Style::new().fg(theme.subtle)
+        };
+
+        if style != run_style && !run.is_empty() {
+            out.push_str(&run_style.apply(&run));
+            run.clear();
+        }
+        run_style = style;
+        run.extend(&chars[start..i]);
+    }
+    if !run.is_empty() {
+        out.push_str(&run_style.apply(&run));
+    }
+    out
+}
+
+/// Matches of `matcher` against a full path, shifted onto its trailing `name`
+fn name_indices(matcher: &QueryMatcher, full: &str, name: &str) -> Vec<usize> {
+    let offset = full.chars().count().saturating_sub(name.chars().count());
+    matcher
+        .indices(full)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|&i| i >= offset)
+        .map(|i| i - offset)
+        .collect()
+}
+
+/// UTC date (YYYY-MM-DD) of a unix timestamp
+pub fn format_date(secs: u64) -> String {
+    // Civil-from-days, Howard Hinnant's algorithm
+    let days = (secs / 86400) as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let doe = days.rem_euclid(146_097);
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Compact "how long ago", e.g. "5m ago" or "3d ago"
+pub fn format_age(secs: u64) -> String {
+    match secs {
+        0..=59 => "just now".to_string(),
+        60..=3599 => format!("{}m ago", secs / 60),
+        3600..=86399 => format!("{}h ago", secs / 3600),
+        _ => format!("{}d ago", secs / 86400),
+    }
+}
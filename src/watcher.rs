@@ -0,0 +1,141 @@
+// ============================================================================
+// Watcher - inotify-backed cache refresh for apps and the file index
+// ============================================================================
+
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant};
+
+use crate::config::AppsConfig;
+use crate::db::Database;
+use crate::error::Result;
+use crate::index::{update_file_index, SearchScope};
+use crate::system::{application_dirs, get_cache_path, rebuild_app_cache};
+
+/// Wait this long without events before acting on a burst of changes
+const QUIET_PERIOD: Duration = Duration::from_millis(500);
+/// Never hold off longer than this, even if a directory keeps churning
+const MAX_BATCH: Duration = Duration::from_secs(5);
+
+/// Non-recursive watches on an explicit set of directories
+pub struct DirWatcher {
+    watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    watched: HashSet<PathBuf>,
+}
+
+impl DirWatcher {
+    pub fn new() -> notify::Result<Self> {
+        let (tx, rx) = mpsc::channel();
+        Ok(Self {
+            watcher: notify::recommended_watcher(tx)?,
+            events: rx,
+            watched: HashSet::new(),
+        })
+    }
+
+    /// Watch exactly `dirs`, adding and dropping watches as the set changes
+    pub fn set_dirs(&mut self, dirs: impl IntoIterator<Item = PathBuf>) {
+        let wanted: HashSet<PathBuf> = dirs.into_iter().filter(|d| d.is_dir()).collect();
+
+        for stale in self.watched.difference(&wanted) {
+            let _ = self.watcher.unwatch(stale);
+        }
+
+        let mut watched = HashSet::with_capacity(wanted.len());
+        for dir in wanted {
+            let ok = self.watched.contains(&dir)
+                || self
+                    .watcher
+                    .watch(&dir, RecursiveMode::NonRecursive)
+                    .inspect_err(|e| tracing::warn!("watching {} failed: {}", dir.display(), e))
+                    .is_ok();
+            if ok {
+                watched.insert(dir);
+            }
+        }
+        self.watched = watched;
+    }
+
+    /// Block until a burst of changes settles, returning the touched paths.
+    /// Returns None once the watcher backend shuts down.
+    pub fn wait_for_changes(&self) -> Option<Vec<PathBuf>> {
+        let mut changed = Vec::new();
+
+        // Block for the first relevant event
+        while changed.is_empty() {
+            let event = self.events.recv().ok()?;
+            collect_paths(event, &mut changed);
+        }
+
+        // Then coalesce everything that follows closely
+        let started = Instant::now();
+        while started.elapsed() < MAX_BATCH {
+            match self.events.recv_timeout(QUIET_PERIOD) {
+                Ok(event) => collect_paths(event, &mut changed),
+                Err(RecvTimeoutError::Timeout) => break,
+                Err(RecvTimeoutError::Disconnected) => return None,
+            }
+        }
+
+        // Our own cache writes would otherwise trigger endless refreshes
+        let cache_dir = get_cache_path("");
+        changed.retain(|p| !p.starts_with(&cache_dir));
+
+        Some(changed)
+    }
+}
+
+fn collect_paths(event: notify::Result<Event>, out: &mut Vec<PathBuf>) {
+    if let Ok(event) = event {
+        // Opening or reading files changes nothing we cache
+        if !matches!(event.kind, EventKind::Access(_)) {
+            out.extend(event.paths);
+        }
+    }
+}
+
+/// `rula --watch`: keep the app cache and file index fresh until killed
+pub fn run_daemon(scope: &SearchScope, apps: &AppsConfig) -> Result<()> {
+    let db = Database::new()?;
+
+    // Start from an up-to-date state so the first launch is already warm
+    rebuild_app_cache(&db, apps)?;
+    let mut index = update_file_index(scope)?;
+
+    let app_dirs = application_dirs();
+    let mut watcher = DirWatcher::new()?;
+    watcher.set_dirs(app_dirs.iter().cloned().chain(index.dir_paths()));
+
+    println!(
+        "Watching {} application dirs and {} indexed dirs...",
+        app_dirs.len(),
+        index.dir_paths().len()
+    );
+
+    while let Some(changed) = watcher.wait_for_changes() {
+        if changed.is_empty() {
+            continue;
+        }
+
+        let apps_changed = changed
+            .iter()
+            .any(|p| app_dirs.iter().any(|d| p.starts_with(d)));
+        let files_changed = changed.iter().any(|p| scope.contains(p));
+
+        if apps_changed {
+            rebuild_app_cache(&db, apps)?;
+        }
+
+        if files_changed {
+            // Only directories whose mtime moved get re-listed
+            index = index.refresh(scope);
+            index.save()?;
+            watcher.set_dirs(app_dirs.iter().cloned().chain(index.dir_paths()));
+        }
+    }
+
+    Ok(())
+}